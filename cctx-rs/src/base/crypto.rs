@@ -0,0 +1,51 @@
+//!
+//! Hash and signature helpers used to sign private api requests
+//! 
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+type HmacSha256 = Hmac<Sha256>;
+type HmacSha384 = Hmac<Sha384>;
+type HmacSha512 = Hmac<Sha512>;
+
+///
+/// SHA256 digest of message, raw bytes
+/// 
+pub fn sha256(message: &[u8]) -> Vec<u8> {
+    Sha256::digest(message).to_vec()
+}
+
+///
+/// HMAC-SHA256 of message with secret as key, hex encoded (lowercase)
+/// 
+pub fn hmac_sha256_hex(secret: &[u8], message: &[u8]) -> String {
+    let mut mac = HmacSha256::new_varkey(secret).expect("HMAC can take key of any size");
+    mac.input(message);
+    hex::encode(mac.result().code())
+}
+
+///
+/// HMAC-SHA384 of message with secret as key, hex encoded (lowercase)
+/// 
+pub fn hmac_sha384_hex(secret: &[u8], message: &[u8]) -> String {
+    let mut mac = HmacSha384::new_varkey(secret).expect("HMAC can take key of any size");
+    mac.input(message);
+    hex::encode(mac.result().code())
+}
+
+///
+/// HMAC-SHA512 of message with secret as key, raw bytes
+/// 
+pub fn hmac_sha512(secret: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha512::new_varkey(secret).expect("HMAC can take key of any size");
+    mac.input(message);
+    mac.result().code().to_vec()
+}
+
+pub fn base64_encode(data: &[u8]) -> String {
+    base64::encode(data)
+}
+
+pub fn base64_decode(data: &str) -> Result<Vec<u8>, base64::DecodeError> {
+    base64::decode(data)
+}
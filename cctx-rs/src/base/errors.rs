@@ -2,13 +2,16 @@
 //! CCXT errors binding, we want to keep same e
 //! 
 
-use failure::Fail;
+use failure::{Fail, Error};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
 use hyper::Error as HyperError;
 use serde_json::Error as SerdeError;
 
 impl Into<CCXTError> for i32 {
     fn into(self) -> CCXTError {
-        println!("Recievedf {}", self);
         match self {
             422 =>  CCXTError::ExchangeError,
             418 =>  CCXTError::DDoSProtection,
@@ -44,7 +47,7 @@ pub enum CCXTLoadingError {
     }
 }
 
-#[derive(Debug, Fail)]
+#[derive(Debug, Fail, Clone, Copy, PartialEq)]
 pub enum CCXTError {    
     #[fail(display = "Undefined error")]
     Undefined,
@@ -131,6 +134,132 @@ pub enum CCXTError {
     OrderImmediatelyFillable   
 }
 
+///
+/// Raised by the connector when the exchange reply with a non 2xx status code
+/// kind is the status mapped through the i32 -> CCXTError table
+/// 
+#[derive(Debug, Fail)]
+#[fail(display = "{} (http status {}): {}", kind, status, body)]
+pub struct CCXTHttpError {
+    pub kind: CCXTError,
+    pub status: u16,
+    pub body: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl CCXTHttpError {
+    pub fn new(status: u16, body: String) -> Self {
+        Self::with_kind((status as i32).into(), status, body)
+    }
+
+    pub fn with_kind(kind: CCXTError, status: u16, body: String) -> Self {
+        let mut kind = kind;
+        if kind == CCXTError::ExchangeNotAvailable {
+            let lowercase = body.to_lowercase();
+            if ["cloudflare", "incapsula", "overload", "ddos"].iter().any(|pattern| lowercase.contains(pattern)) {
+                kind = CCXTError::DDoSProtection;
+            }
+        }
+        CCXTHttpError {kind, status, body, retry_after: None}
+    }
+}
+
+///
+/// Return the CCXTError of a failure Error, unwrapping CCXTHttpError if needed
+/// 
+pub fn error_kind(error: &Error) -> Option<CCXTError> {
+    if let Some(http_error) = error.downcast_ref::<CCXTHttpError>() {
+        return Some(http_error.kind);
+    }
+    error.downcast_ref::<CCXTError>().cloned()
+}
+
+impl FromStr for CCXTError {
+    type Err = CCXTLoadingError;
+
+    ///
+    /// Parse a CCXTError from its name (ex: "InsufficientFunds"), used to load exchanges exceptions
+    /// 
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Ok(match name {
+            "ExchangeError" => CCXTError::ExchangeError,
+            "NotSupported" => CCXTError::NotSupported,
+            "AuthenticationError" => CCXTError::AuthenticationError,
+            "PermissionDenied" => CCXTError::PermissionDenied,
+            "AccountSuspended" => CCXTError::AccountSuspended,
+            "InsufficientFunds" => CCXTError::InsufficientFunds,
+            "InvalidOrder" => CCXTError::InvalidOrder,
+            "OrderNotFound" => CCXTError::OrderNotFound,
+            "OrderNotCached" => CCXTError::OrderNotCached,
+            "CancelPending" => CCXTError::CancelPending,
+            "NetworkError" => CCXTError::NetworkError,
+            "DDoSProtection" => CCXTError::DDoSProtection,
+            "RequestTimeout" => CCXTError::RequestTimeout,
+            "ExchangeNotAvailable" => CCXTError::ExchangeNotAvailable,
+            "InvalidNonce" => CCXTError::InvalidNonce,
+            "InvalidAddress" => CCXTError::InvalidAddress,
+            "AddressPending" => CCXTError::AddressPending,
+            "ArgumentsRequired" => CCXTError::ArgumentsRequired,
+            "BadRequest" => CCXTError::BadRequest,
+            "BadResponse" => CCXTError::BadResponse,
+            "NullResponse" => CCXTError::NullResponse,
+            "OrderNotFillable" => CCXTError::OrderNotFillable,
+            "OrderImmediatelyFillable" => CCXTError::OrderImmediatelyFillable,
+            _ => return Err(CCXTLoadingError::UndefinedField {field: format!("exceptions->{}", name)}),
+        })
+    }
+}
+
+///
+/// Exchange specific error messages (the "exceptions" section of the exchange json)
+/// exact messages are compared as is, broad ones only have to be contained in the message
+/// 
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeExceptions {
+    pub exact: HashMap<String, CCXTError>,
+    pub broad: Vec<(String, CCXTError)>,
+}
+
+impl ExchangeExceptions {
+    ///
+    /// Same as findBroadlyMatchedKey from the js base Exchange
+    /// 
+    pub fn find_broadly_matched_key(&self, message: &str) -> Option<CCXTError> {
+        self.broad.iter().find(|(key, _)| message.contains(key.as_str())).map(|(_, error)| *error)
+    }
+
+    pub fn find(&self, message: &str) -> Option<CCXTError> {
+        self.exact.get(message).cloned().or_else(|| self.find_broadly_matched_key(message))
+    }
+
+    ///
+    /// Look for a known error in a json response, the messages are searched in the usual
+    /// "error", "error"->"message", "message", "msg" and "code" fields, in error lists ({"error": ["EOrder:Unknown order"]})
+    /// and in top level arrays (["error", 10020, "message"])
+    /// 
+    pub fn find_in_response(&self, response: &Value) -> Option<CCXTError> {
+        if self.exact.is_empty() && self.broad.is_empty() {
+            return None;
+        }
+        let candidates: Vec<&Value> = match response {
+            Value::Object(_) => {
+                let mut candidates = vec![&response["error"], &response["error"]["message"], &response["message"], &response["msg"], &response["code"]];
+                if let Value::Array(errors) = &response["error"] {
+                    candidates.extend(errors.iter());
+                }
+                candidates
+            },
+            Value::Array(values) => values.iter().collect(),
+            _ => vec![response],
+        };
+        candidates.into_iter().filter_map(|candidate| match candidate {
+            Value::String(message) => Some(message.clone()),
+            Value::Number(code) => Some(code.to_string()),
+            _ => None,
+        }).filter_map(|message| self.find(&message)).next()
+    }
+}
+
 impl From<HyperError> for CCXTError {
     fn from(_err: HyperError) -> CCXTError {
         CCXTError::BadResponse//Maybe wrong
@@ -141,4 +270,37 @@ impl From<SerdeError> for CCXTError {
     fn from(_err: SerdeError) -> CCXTError {
         CCXTError::BadResponse
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_error_kind() {
+        assert_eq!(CCXTHttpError::new(429, String::new()).kind, CCXTError::DDoSProtection);
+        assert_eq!(CCXTHttpError::new(401, String::new()).kind, CCXTError::AuthenticationError);
+        assert_eq!(CCXTHttpError::new(503, String::from("Checking your browser - Cloudflare")).kind, CCXTError::DDoSProtection);
+        assert_eq!(CCXTHttpError::new(503, String::from("Service unavailable")).kind, CCXTError::ExchangeNotAvailable);
+        let error: Error = CCXTHttpError::new(504, String::new()).into();
+        assert_eq!(error_kind(&error), Some(CCXTError::RequestTimeout));
+    }
+
+    #[test]
+    fn test_exchange_exceptions() {
+        let mut exceptions = ExchangeExceptions::default();
+        exceptions.exact.insert(String::from("Nonce is too small."), CCXTError::InvalidNonce);
+        exceptions.exact.insert(String::from("-2011"), CCXTError::OrderNotFound);
+        exceptions.broad.push((String::from("not enough exchange balance"), CCXTError::InsufficientFunds));
+        let response: Value = serde_json::from_str(r#"{"message": "Nonce is too small."}"#).unwrap();
+        assert_eq!(exceptions.find_in_response(&response), Some(CCXTError::InvalidNonce));
+        let response: Value = serde_json::from_str(r#"{"error": {"message": "Invalid order: not enough exchange balance for 1 BTC", "name": "HTTPError"}}"#).unwrap();
+        assert_eq!(exceptions.find_in_response(&response), Some(CCXTError::InsufficientFunds));
+        let response: Value = serde_json::from_str(r#"{"code": -2011, "msg": "Unknown order sent."}"#).unwrap();
+        assert_eq!(exceptions.find_in_response(&response), Some(CCXTError::OrderNotFound));
+        let response: Value = serde_json::from_str(r#"{"error": ["EGeneral:Invalid arguments", "Nonce is too small."], "result": {}}"#).unwrap();
+        assert_eq!(exceptions.find_in_response(&response), Some(CCXTError::InvalidNonce));
+        let response: Value = serde_json::from_str(r#"[{"price": 1.0}]"#).unwrap();
+        assert_eq!(exceptions.find_in_response(&response), None);
+    }
+}
@@ -2,17 +2,27 @@
 //! Base exchange traits that will be implemented for all plateformes
 //! 
 use super::errors::*;
+use super::throttle::Throttle;
+use super::retry::RetryPolicy;
 
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
 use std::collections::HashMap;
 use failure::Error;
 use hyper::rt::{Future};
-use serde_json::value::Value;
+use futures::future::{err, ok, loop_fn, Loop};
+use tokio::timer::Delay;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use chrono::naive::NaiveDateTime;
+use std::str::FromStr;
+use serde_json::value::{Value, Index};
+use std::cmp::Ordering;
 use serde_json;
 use hyper::Uri;
 use hyper;
 use std::sync::{Arc, RwLock};
+use std::sync::atomic::{self, AtomicUsize};
 
 macro_rules! try_block {
     ($block:block) => (
@@ -57,12 +67,38 @@ macro_rules! as_i64_or {
         $val.as_i64().unwrap_or($default)
     );
 }
+
+///
+/// Read a float that can be sent either as a json number or as a string (ex: "0.0123")
+/// 
+#[allow(warnings)]
+macro_rules! safe_f64 {
+    ($val:expr) => (
+        $val.as_f64().or_else(|| $val.as_str().and_then(|s| s.parse::<f64>().ok()))
+    );
+}
+
+///
+/// Numbers are converted to strings (ex: integer ids)
+/// 
+#[allow(warnings)]
+macro_rules! safe_string {
+    ($val:expr) => (
+        $val.as_str().map(String::from).or_else(|| $val.as_i64().map(|val| val.to_string()))
+    );
+}
  
 macro_rules! get_api {
     ($m:expr, $api:expr, $route:expr, $($params:expr), *) => ($m.call_api($api, ApiMethod::Get, $route, &[$($params),*]));
     ($m:expr, $api:expr, $route:expr) => ($m.call_api($api, ApiMethod::Get, $route, &[]));
 }
 
+#[allow(unused_macros)]
+macro_rules! post_api {
+    ($m:expr, $api:expr, $route:expr, $($params:expr), *) => ($m.call_api($api, ApiMethod::Post, $route, &[$($params),*]));
+    ($m:expr, $api:expr, $route:expr) => ($m.call_api($api, ApiMethod::Post, $route, &[]));
+}
+
 
 // pub const USER_AGENTS_CHROME: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/62.0.3202.94 Safari/537.36";
 // pub const USER_AGENT_CHROME39: &str = "Mozilla/5.0 (Windows NT 6.1; WOW64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/39.0.2171.71 Safari/537.36";
@@ -77,145 +113,1240 @@ macro_rules! get_api {
 /// 
 
 ///
-/// Http request body (will be json)
+/// Http request body, api parametters are url encoded by default
+/// sign hooks can turn them into a json body when the exchange requires it (see Request::set_json_body)
 /// 
-pub trait RequestBody {}
+#[derive(Debug, Clone)]
+pub enum RequestBody {
+    Empty,
+    UrlEncoded(Vec<String>),
+    Json(Value),
+}
 
-///
-/// Http request parametter
-/// 
-pub struct RequestParam<'a> {
-    _key: &'a str,
-    _value: &'a str,
+impl RequestBody {
+    pub fn content_type(&self) -> Option<&'static str> {
+        match self {
+            RequestBody::Empty => None,
+            RequestBody::UrlEncoded(_) => Some("application/x-www-form-urlencoded"),
+            RequestBody::Json(_) => Some("application/json"),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        match self {
+            RequestBody::Empty => String::new(),
+            RequestBody::UrlEncoded(params) => params.join("&"),
+            RequestBody::Json(json) => json.to_string(),
+        }
+    }
+
+    ///
+    /// Convert "key=value" parametters to a json object
+    /// values that are valid json (numbers, booleans, objects) are kept as is, others are sent as strings
+    /// 
+    pub fn json_from_params(params: &[String]) -> RequestBody {
+        let mut object = serde_json::Map::new();
+        for param in params {
+            let mut split = param.splitn(2, '=');
+            let key = split.next().unwrap_or_default();
+            let value = split.next().unwrap_or_default();
+            object.insert(String::from(key), serde_json::from_str(value).unwrap_or_else(|_| Value::String(String::from(value))));
+        }
+        RequestBody::Json(Value::Object(object))
+    }
 }
 
 ///
 /// Http request type enum with is parametters
+/// Get and Delete parametters are sent in the query, Post and Put ones in the body
 /// 
-pub enum RequestMethod<'a> {
+#[derive(Debug, Clone)]
+pub enum RequestMethod {
     Get(Vec<String>),
-    Post(Vec<RequestParam<'a>>, &'a RequestBody),
+    Post(Vec<String>, RequestBody),
+    Put(Vec<String>, RequestBody),
+    Delete(Vec<String>, RequestBody),
 }
 
-pub struct Request<'a> {
+///
+/// exceptions is the exchange error table, the connector use it to turn error replies into CCXTError
+/// timeout is the maximum duration of the request, the connector fail with CCXTError::RequestTimeout after it
+/// 
+#[derive(Debug, Clone)]
+pub struct Request {
     pub path: hyper::Uri,
-    pub method: RequestMethod<'a>
+    pub method: RequestMethod,
+    pub headers: Vec<(String, String)>,
+    pub exceptions: Option<Arc<ExchangeExceptions>>,
+    pub timeout: Option<Duration>,
 }
 
-impl <'a>Request<'a> {
-    pub fn new(path: Uri, method: RequestMethod<'a>) -> Self {
+impl Request {
+    pub fn new(path: Uri, method: RequestMethod) -> Self {
         Request {
             path: path,
             method,
+            headers: Vec::new(),
+            exceptions: None,
+            timeout: None,
+        }
+    }
+
+    pub fn method_name(&self) -> &'static str {
+        match self.method {
+            RequestMethod::Get(_) => "GET",
+            RequestMethod::Post(_, _) => "POST",
+            RequestMethod::Put(_, _) => "PUT",
+            RequestMethod::Delete(_, _) => "DELETE",
+        }
+    }
+
+    pub fn query_params(&self) -> &Vec<String> {
+        match &self.method {
+            RequestMethod::Get(params) => params,
+            RequestMethod::Post(params, _) => params,
+            RequestMethod::Put(params, _) => params,
+            RequestMethod::Delete(params, _) => params,
+        }
+    }
+
+    pub fn body(&self) -> &RequestBody {
+        static EMPTY: RequestBody = RequestBody::Empty;
+        match &self.method {
+            RequestMethod::Get(_) => &EMPTY,
+            RequestMethod::Post(_, body) => body,
+            RequestMethod::Put(_, body) => body,
+            RequestMethod::Delete(_, body) => body,
+        }
+    }
+
+    ///
+    /// Replace an url encoded body by its json equivalent
+    /// 
+    pub fn set_json_body(&mut self) {
+        match &mut self.method {
+            RequestMethod::Post(_, body) | RequestMethod::Put(_, body) | RequestMethod::Delete(_, body) => {
+                if let RequestBody::UrlEncoded(params) = body {
+                    *body = RequestBody::json_from_params(params);
+                }
+            },
+            RequestMethod::Get(_) => {},
+        }
+    }
+
+    ///
+    /// Url encoded query (with the leading '?') or an empty string if there is no get parametters
+    /// 
+    pub fn query(&self) -> String {
+        let params = self.query_params();
+        if params.is_empty() { String::new() } else { format!("?{}", params.join("&")) }
+    }
+
+    ///
+    /// Path and query as it will be sent (ex: /api/v1/order?symbol=XBTUSD), usefull to sign requests
+    /// 
+    pub fn path_and_query(&self) -> String {
+        format!("{}{}", self.path.path(), self.query())
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}{}", self.path, self.query())
+    }
+
+    pub fn add_header(&mut self, key: &str, value: &str) {
+        self.headers.push((String::from(key), String::from(value)));
+    }
+
+    ///
+    /// Add a "key=value" parametter where the method expect it (query for Get and Delete, body for Post and Put)
+    /// 
+    pub fn push_param(&mut self, param: String) {
+        match &mut self.method {
+            RequestMethod::Get(params) | RequestMethod::Delete(params, _) => params.push(param),
+            RequestMethod::Post(_, body) | RequestMethod::Put(_, body) => match body {
+                RequestBody::UrlEncoded(params) => params.push(param),
+                _ => *body = RequestBody::UrlEncoded(vec![param]),
+            },
+        }
+    }
+
+    ///
+    /// Same as push_param but insert the parametter at index (0 to put it first, ex: Kraken nonce)
+    /// 
+    pub fn insert_param(&mut self, index: usize, param: String) {
+        match &mut self.method {
+            RequestMethod::Get(params) | RequestMethod::Delete(params, _) => params.insert(index.min(params.len()), param),
+            RequestMethod::Post(_, body) | RequestMethod::Put(_, body) => match body {
+                RequestBody::UrlEncoded(params) => params.insert(index.min(params.len()), param),
+                _ => *body = RequestBody::UrlEncoded(vec![param]),
+            },
+        }
+    }
+
+    ///
+    /// Url encoded parametters without the leading '?', from the query or the body depending on the method
+    /// 
+    pub fn encoded_params(&self) -> String {
+        match &self.method {
+            RequestMethod::Get(params) | RequestMethod::Delete(params, _) => params.join("&"),
+            RequestMethod::Post(_, body) | RequestMethod::Put(_, body) => body.encode(),
+        }
+    }
+}
+
+///
+/// Http request response value (Parsed json)
+/// @TODO maybe do not parse the json and parse it later strongely typed
+/// 
+pub enum RequestResponse {
+    Error,
+    Success(Value),
+}
+
+pub type ConnectorFuture<T> = Box<Future<Item=T, Error=Error> + Send>;
+
+pub trait Connector: Send + 'static {
+    fn request(&self, request: Request) -> ConnectorFuture<Value>;
+}
+
+///
+/// Api keys of an account, only the ones listed in the exchange "requiredCredentials" are mandatory
+/// 
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    pub api_key: Option<String>,
+    pub secret: Option<String>,
+    pub uid: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(api_key: &str, secret: &str) -> Self {
+        Credentials {
+            api_key: Some(String::from(api_key)),
+            secret: Some(String::from(secret)),
+            uid: None,
+            password: None,
+        }
+    }
+
+    fn get(&self, name: &str) -> Option<&String> {
+        match name {
+            "apiKey" => self.api_key.as_ref(),
+            "secret" => self.secret.as_ref(),
+            "uid" => self.uid.as_ref(),
+            "password" => self.password.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+///
+/// Per exchange hook called on every request before it is sent by the connector
+/// It receive the api name (ex: "private") and add nonce, signature and auth headers to the request
+/// 
+pub type SignHook<C> = fn(&Exchange<C>, &str, &mut Request) -> Result<(), Error>;
+
+
+///
+///
+/// The generic Exchange wrapper
+/// 
+/// 
+
+pub type CCXTFut<T> = Box<Future<Item=T, Error=Error> + Send>;
+
+pub enum CCXTSymbol {
+    Undefined,
+}
+
+///
+/// amount (min, max)
+/// price (min, max)
+/// cost (min, max)
+#[derive(Debug, Clone)]
+pub struct MarketLimits {
+    pub amount: (f64, f64),
+    pub price: (f64, f64),
+    pub cost: (f64, f64),
+}
+
+impl MarketLimits {
+    pub fn new(amount: (f64, f64), price: (f64, f64), cost: (f64, f64)) -> Self {
+        MarketLimits {amount, price, cost}
+    }
+}
+
+///
+/// Swaps are perpetual contracts, futures have an expiry
+/// Index are reference prices that can't be traded (ex: Bitmex .BXBT)
+/// 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketType {
+    Spot,
+    Future,
+    Swap,
+    Option,
+    Index,
+}
+
+///
+/// base and quote are the unified currency codes (see Exchange::common_currency_code)
+/// base_id and quote_id are the raw exchange ids
+/// Derivatives fields are None for spot markets:
+/// linear is Some(false) for inverse contracts (margined and settled in base currency)
+/// settle is the unified code of the settlement currency, expiry a timestamp in milliseconds
+/// 
+#[derive(Debug, Clone)]
+pub struct Market {
+    pub id: String,
+    pub symbol: String,
+    pub base: String,
+    pub quote: String,
+    pub base_id: String,
+    pub quote_id: String,//Todo make enum referencin all symbols
+    pub active: bool,
+    pub precision: (f64,f64),
+    pub limits: MarketLimits,
+    pub market_type: MarketType,
+    pub linear: Option<bool>,
+    pub settle: Option<String>,
+    pub contract_size: Option<f64>,
+    pub expiry: Option<i64>,
+    pub underlying_index: Option<String>,
+    pub info: Option<Value>,//Remove it if it's possible
+}
+
+///
+/// deposit (min, max)
+/// withdraw (min, max)
+#[derive(Debug, Clone)]
+pub struct CurrencyLimits {
+    pub deposit: (f64, f64),
+    pub withdraw: (f64, f64),
+}
+
+impl CurrencyLimits {
+    pub fn new(deposit: (f64, f64), withdraw: (f64, f64)) -> Self {
+        CurrencyLimits {deposit, withdraw}
+    }
+}
+
+///
+/// code is the unified currency code, id the one used by the exchange
+/// precision is a number of decimals
+/// 
+#[derive(Debug, Clone)]
+pub struct Currency {
+    pub id: String,
+    pub code: String,
+    pub active: bool,
+    pub precision: f64,
+    pub deposit_fee: Option<f64>,
+    pub withdraw_fee: Option<f64>,
+    pub limits: CurrencyLimits,
+    pub info: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Ohlcv {
+    pub timestamp: i64,
+    pub open: f64,
+    pub highest: f64,
+    pub lowest: f64,
+    pub losing: f64,
+    pub volume: f64,
+}
+
+#[repr(u32)]
+pub enum CandleTime {
+    _1M=1,
+    _2M=2,
+    _3M=3,
+    _4M=4,
+    _5M=5,
+    _10M=10,
+    _15M=15,
+    _20M=20,
+    _30M=30,
+    _1H=60,
+    _2H=60*2,
+    _3H=60*3,
+    _4H=60*4,
+    _5H=60*5,
+    _6H=60*6,
+    _8H=60*8,
+    _12H=60*12,
+    _1D=60*24,
+    _2D=60*24*2,
+    _3D=60*24*3,
+    _1W=60*24*7,
+    _1MONTH=60*24*30,
+}
+
+///
+/// Unified ticker, timestamp is in milliseconds
+/// Every field that the exchange does not provide is left to None
+/// 
+#[derive(Debug, Clone)]
+pub struct Ticker {
+    pub symbol: String,
+    pub timestamp: i64,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub bid: Option<f64>,
+    pub bid_volume: Option<f64>,
+    pub ask: Option<f64>,
+    pub ask_volume: Option<f64>,
+    pub vwap: Option<f64>,
+    pub open: Option<f64>,
+    pub close: Option<f64>,
+    pub last: Option<f64>,
+    pub previous_close: Option<f64>,
+    pub change: Option<f64>,
+    pub percentage: Option<f64>,
+    pub average: Option<f64>,
+    pub base_volume: Option<f64>,
+    pub quote_volume: Option<f64>,
+    pub info: Option<Value>,
+}
+
+///
+/// Order book, bids and asks are (price, amount) levels
+/// bids are sorted by descending price and asks by ascending price
+/// 
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+    pub timestamp: Option<i64>,
+    pub nonce: Option<i64>,
+}
+
+impl OrderBook {
+    pub fn new(bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>, timestamp: Option<i64>) -> Self {
+        let mut orderbook = OrderBook {bids, asks, timestamp, nonce: None};
+        orderbook.sort();
+        orderbook
+    }
+
+    pub fn sort(&mut self) {
+        self.bids.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+        self.asks.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl FromStr for OrderSide {
+    type Err = CCXTError;
+
+    fn from_str(side: &str) -> Result<Self, Self::Err> {
+        match side.to_lowercase().as_ref() {
+            "buy" => Ok(OrderSide::Buy),
+            "sell" => Ok(OrderSide::Sell),
+            _ => Err(CCXTError::BadResponse),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TakerOrMaker {
+    Taker,
+    Maker,
+}
+
+///
+/// Fee paid for a trade or a transaction
+/// rate is a fraction of the cost (0.001 = 0.1%)
+/// 
+#[derive(Debug, Clone)]
+pub struct Fee {
+    pub cost: f64,
+    pub currency: Option<String>,
+    pub rate: Option<f64>,
+}
+
+///
+/// Public or private trade, timestamp is in milliseconds
+/// order is the id of the order that generated the trade when the exchange provide it
+/// 
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub id: Option<String>,
+    pub timestamp: i64,
+    pub symbol: String,
+    pub order: Option<String>,
+    pub side: Option<OrderSide>,
+    pub taker_or_maker: Option<TakerOrMaker>,
+    pub price: f64,
+    pub amount: f64,
+    pub cost: f64,
+    pub fee: Option<Fee>,
+    pub info: Option<Value>,
+}
+
+///
+/// Funds of one currency, total = free + used
+/// 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CurrencyBalance {
+    pub free: f64,
+    pub used: f64,
+    pub total: f64,
+}
+
+impl CurrencyBalance {
+    ///
+    /// Build a balance from the values returned by the exchange, a missing value is deduced from the two others
+    /// 
+    pub fn new(free: Option<f64>, used: Option<f64>, total: Option<f64>) -> Self {
+        match (free, used, total) {
+            (Some(free), Some(used), None) => CurrencyBalance {free, used, total: free + used},
+            (Some(free), None, Some(total)) => CurrencyBalance {free, used: total - free, total},
+            (None, Some(used), Some(total)) => CurrencyBalance {free: total - used, used, total},
+            (free, used, total) => CurrencyBalance {
+                free: free.unwrap_or(0.0),
+                used: used.unwrap_or(0.0),
+                total: total.unwrap_or(free.unwrap_or(0.0) + used.unwrap_or(0.0)),
+            },
         }
     }
 }
 
 ///
-/// Http request response value (Parsed json)
-/// @TODO maybe do not parse the json and parse it later strongely typed
+/// Account balance indexed by unified currency code
+/// 
+#[derive(Debug, Clone, Default)]
+pub struct Balance {
+    pub currencies: HashMap<String, CurrencyBalance>,
+    pub info: Option<Value>,
+}
+
+impl Balance {
+    pub fn get(&self, code: &str) -> Option<&CurrencyBalance> {
+        self.currencies.get(code)
+    }
+
+    pub fn free(&self) -> HashMap<String, f64> {
+        self.currencies.iter().map(|(code, balance)| (code.clone(), balance.free)).collect()
+    }
+
+    pub fn used(&self) -> HashMap<String, f64> {
+        self.currencies.iter().map(|(code, balance)| (code.clone(), balance.used)).collect()
+    }
+
+    pub fn total(&self) -> HashMap<String, f64> {
+        self.currencies.iter().map(|(code, balance)| (code.clone(), balance.total)).collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderType {
+    Market,
+    Limit,
+    Stop,
+    StopLimit,
+    Other(String),
+}
+
+impl FromStr for OrderType {
+    type Err = CCXTError;
+
+    ///
+    /// Never fail, exchange specific types are kept as OrderType::Other
+    /// 
+    fn from_str(order_type: &str) -> Result<Self, Self::Err> {
+        Ok(match order_type.to_lowercase().replace(" ", "").replace("-", "").replace("_", "").as_ref() {
+            "market" => OrderType::Market,
+            "limit" => OrderType::Limit,
+            "stop" | "stopmarket" => OrderType::Stop,
+            "stoplimit" => OrderType::StopLimit,
+            _ => OrderType::Other(String::from(order_type)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderStatus {
+    Open,
+    Closed,
+    Canceled,
+    Expired,
+    Rejected,
+}
+
+///
+/// Unified order, timestamps are in milliseconds
+/// amount, filled and remaining are in base currency (contracts for derivatives), cost in quote currency
+/// 
+#[derive(Debug, Clone)]
+pub struct Order {
+    pub id: String,
+    pub client_order_id: Option<String>,
+    pub timestamp: Option<i64>,
+    pub last_trade_timestamp: Option<i64>,
+    pub symbol: String,
+    pub order_type: OrderType,
+    pub side: OrderSide,
+    pub price: Option<f64>,
+    pub amount: f64,
+    pub filled: f64,
+    pub remaining: f64,
+    pub cost: f64,
+    pub status: OrderStatus,
+    pub fee: Option<Fee>,
+    pub trades: Vec<Trade>,
+    pub info: Option<Value>,
+}
+
+///
+/// How edit_order modified an order
+/// Replaced orders have a new id, the old one is canceled
+/// 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditMethod {
+    Amend,
+    CancelReplace,
+}
+
+#[derive(Debug, Clone)]
+pub struct EditedOrder {
+    pub order: Order,
+    pub method: EditMethod,
+}
+
+///
+/// tag is the memo / payment id required by some currencies (ex: XRP, XMR)
+/// 
+#[derive(Debug, Clone)]
+pub struct DepositAddress {
+    pub currency: String,
+    pub address: String,
+    pub tag: Option<String>,
+    pub info: Option<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WithdrawResponse {
+    pub id: String,
+    pub info: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionType {
+    Deposit,
+    Withdrawal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransactionStatus {
+    Pending,
+    Ok,
+    Failed,
+    Canceled,
+}
+
+///
+/// Deposit or withdrawal, amount is always positive and the fee is paid in the transaction currency
+/// txid is the on-chain transaction hash when it's known
+/// 
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub id: String,
+    pub txid: Option<String>,
+    pub timestamp: Option<i64>,
+    pub updated: Option<i64>,
+    pub address: Option<String>,
+    pub tag: Option<String>,
+    pub transaction_type: TransactionType,
+    pub amount: f64,
+    pub currency: String,
+    pub status: TransactionStatus,
+    pub fee: Option<Fee>,
+    pub info: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LedgerDirection {
+    In,
+    Out,
+}
+
+///
+/// What caused a balance change, exchange specific types are kept as LedgerEntryType::Other
+/// 
+#[derive(Debug, Clone, PartialEq)]
+pub enum LedgerEntryType {
+    Trade,
+    Transaction,
+    Fee,
+    Margin,
+    Transfer,
+    Referral,
+    Rebate,
+    Other(String),
+}
+
+///
+/// Balance change of an account, amount is always positive and the direction tell if it was credited
+/// before and after are the balances of the currency around the change when the exchange report them
+/// 
+#[derive(Debug, Clone)]
+pub struct LedgerEntry {
+    pub id: String,
+    pub timestamp: Option<i64>,
+    pub direction: LedgerDirection,
+    pub account: Option<String>,
+    pub reference_id: Option<String>,
+    pub reference_account: Option<String>,
+    pub entry_type: LedgerEntryType,
+    pub currency: String,
+    pub amount: f64,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+    pub status: Option<TransactionStatus>,
+    pub fee: Option<Fee>,
+    pub info: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PositionSide {
+    Long,
+    Short,
+}
+
+///
+/// Position on a derivative market, contracts is always positive and side is None when the position is flat
+/// Pnl, margin and notional are in the settlement currency of the market
+/// leverage is None for cross margin positions
+/// 
+#[derive(Debug, Clone)]
+pub struct Position {
+    pub symbol: String,
+    pub timestamp: Option<i64>,
+    pub side: Option<PositionSide>,
+    pub contracts: f64,
+    pub entry_price: Option<f64>,
+    pub mark_price: Option<f64>,
+    pub liquidation_price: Option<f64>,
+    pub leverage: Option<f64>,
+    pub notional: Option<f64>,
+    pub margin: Option<f64>,
+    pub unrealized_pnl: Option<f64>,
+    pub realized_pnl: Option<f64>,
+    pub info: Option<Value>,
+}
+
+///
+/// Funding rate of a swap, timestamp is the funding time and interval the time between two fundings (milliseconds)
+/// predicted_rate is the estimation of the next rate when the exchange publish it
+/// 
+#[derive(Debug, Clone)]
+pub struct FundingRate {
+    pub symbol: String,
+    pub timestamp: Option<i64>,
+    pub rate: f64,
+    pub predicted_rate: Option<f64>,
+    pub interval: Option<i64>,
+    pub info: Option<Value>,
+}
+
+///
+/// Entries that can be filtered by filter_by_since_limit
+/// 
+pub trait Timestamped {
+    fn timestamp(&self) -> Option<i64>;
+}
+
+///
+/// Entries that can be filtered by filter_by_symbol_since_limit
+/// 
+pub trait BySymbol: Timestamped {
+    fn symbol(&self) -> &str;
+}
+
+///
+/// Entries that can be filtered by filter_by_currency_since_limit
+/// 
+pub trait ByCurrency: Timestamped {
+    fn currency(&self) -> &str;
+}
+
+impl Timestamped for Trade {
+    fn timestamp(&self) -> Option<i64> {
+        Some(self.timestamp)
+    }
+}
+
+impl BySymbol for Trade {
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl Timestamped for Order {
+    fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+}
+
+impl BySymbol for Order {
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+impl Timestamped for Transaction {
+    fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+}
+
+impl ByCurrency for Transaction {
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+}
+
+impl Timestamped for LedgerEntry {
+    fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+}
+
+impl ByCurrency for LedgerEntry {
+    fn currency(&self) -> &str {
+        &self.currency
+    }
+}
+
+impl Timestamped for FundingRate {
+    fn timestamp(&self) -> Option<i64> {
+        self.timestamp
+    }
+}
+
+impl BySymbol for FundingRate {
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+}
+
+pub type FetchOhlcvResult = CCXTFut<Vec<Ohlcv>>;
+pub type LoadMarketResult = CCXTFut<Arc<RwLock<Option<HashMap<String, Market>>>>>;
+pub type FetchTickerResult = CCXTFut<Ticker>;
+pub type FetchTickersResult = CCXTFut<HashMap<String, Ticker>>;
+pub type FetchOrderBookResult = CCXTFut<OrderBook>;
+pub type FetchTradesResult = CCXTFut<Vec<Trade>>;
+pub type FetchCurrenciesResult = CCXTFut<HashMap<String, Currency>>;
+pub type FetchBalanceResult = CCXTFut<Balance>;
+pub type FetchPartialBalanceResult = CCXTFut<HashMap<String, f64>>;
+pub type OrderResult = CCXTFut<Order>;
+pub type FetchOrdersResult = CCXTFut<Vec<Order>>;
+pub type EditOrderResult = CCXTFut<EditedOrder>;
+pub type DepositAddressResult = CCXTFut<DepositAddress>;
+pub type WithdrawResult = CCXTFut<WithdrawResponse>;
+pub type FetchTransactionsResult = CCXTFut<Vec<Transaction>>;
+pub type FetchLedgerResult = CCXTFut<Vec<LedgerEntry>>;
+pub type PositionResult = CCXTFut<Position>;
+pub type FetchPositionsResult = CCXTFut<Vec<Position>>;
+pub type FetchFundingRateResult = CCXTFut<FundingRate>;
+pub type FetchFundingHistoryResult = CCXTFut<Vec<FundingRate>>;
+
+///
+/// Unified api, every method that is not implemented by an exchange fail with CCXTError::NotSupported
+/// 
+pub trait ExchangeTrait {
+    fn fetch_ohlcv(&self, symbol: &str, timeframe: CandleTime, since: i64, limit: i64) -> FetchOhlcvResult;
+    fn fetch_markets(&mut self) -> LoadMarketResult;
+
+    fn fetch_ticker(&self, _symbol: &str) -> FetchTickerResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Fetch tickers of all markets, or only the given symbols, indexed by symbol
+    /// 
+    fn fetch_tickers(&self, _symbols: Option<&[&str]>) -> FetchTickersResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Fetch the order book of a market, limit is the maximum depth of each side
+    /// 
+    fn fetch_order_book(&self, _symbol: &str, _limit: Option<i64>) -> FetchOrderBookResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Fetch the last public trades of a market, since is a timestamp in milliseconds
+    /// 
+    fn fetch_trades(&self, _symbol: &str, _since: Option<i64>, _limit: Option<i64>) -> FetchTradesResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Trades of the account sorted by timestamp, with the fee when the exchange report it
+    /// 
+    fn fetch_my_trades(&self, _symbol: Option<&str>, _since: Option<i64>, _limit: Option<i64>) -> FetchTradesResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Fetch all currencies of the exchange indexed by unified code
+    /// 
+    fn fetch_currencies(&self) -> FetchCurrenciesResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    fn fetch_balance(&self) -> FetchBalanceResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    fn fetch_free_balance(&self) -> FetchPartialBalanceResult {
+        Box::new(self.fetch_balance().map(|balance| balance.free()))
+    }
+
+    fn fetch_used_balance(&self) -> FetchPartialBalanceResult {
+        Box::new(self.fetch_balance().map(|balance| balance.used()))
+    }
+
+    fn fetch_total_balance(&self) -> FetchPartialBalanceResult {
+        Box::new(self.fetch_balance().map(|balance| balance.total()))
+    }
+
+    ///
+    /// Place an order, price is required by limit orders and ignored by market ones
+    /// 
+    fn create_order(&self, _symbol: &str, _order_type: OrderType, _side: OrderSide, _amount: f64, _price: Option<f64>) -> OrderResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Some exchanges need the symbol of the order to cancel or fetch it
+    /// 
+    fn cancel_order(&self, _id: &str, _symbol: Option<&str>) -> OrderResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    fn fetch_order(&self, _id: &str, _symbol: Option<&str>) -> OrderResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Orders sorted by timestamp, symbol is required by some exchanges
+    /// 
+    fn fetch_orders(&self, _symbol: Option<&str>, _since: Option<i64>, _limit: Option<i64>) -> FetchOrdersResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Default to fetch_orders filtered by status, the limit is applied after the filter
+    /// 
+    fn fetch_open_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        Box::new(self.fetch_orders(symbol, since, None)
+            .map(move |orders| filter_by_status_limit(orders, OrderStatus::Open, limit)))
+    }
+
+    fn fetch_closed_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        Box::new(self.fetch_orders(symbol, since, None)
+            .map(move |orders| filter_by_status_limit(orders, OrderStatus::Closed, limit)))
+    }
+
+    ///
+    /// Same as the exchange "has" capability map, default to false
+    /// 
+    fn has(&self, _capability: &str) -> bool {
+        false
+    }
+
+    ///
+    /// Native order amendment, only called by edit_order when the exchange has "editOrder"
+    /// 
+    fn amend_order(&self, _id: &str, _symbol: &str, _amount: Option<f64>, _price: Option<f64>) -> OrderResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Amend the order when the exchange support it, otherwise cancel it then create a new one
+    /// The new order is only placed once the cancellation is confirmed so both can't be live at the same time
+    /// 
+    fn edit_order(&self, id: &str, symbol: &str, order_type: OrderType, side: OrderSide, amount: f64, price: Option<f64>) -> EditOrderResult
+        where Self: Clone + Send + Sized + 'static {
+        if self.has("editOrder") {
+            return Box::new(self.amend_order(id, symbol, Some(amount), price)
+                .map(|order| EditedOrder { order, method: EditMethod::Amend }));
+        }
+        let exchange = self.clone();
+        let symbol = String::from(symbol);
+        Box::new(self.cancel_order(id, Some(symbol.as_str()))
+            .and_then(move |_| exchange.create_order(&symbol, order_type, side, amount, price))
+            .map(|order| EditedOrder { order, method: EditMethod::CancelReplace }))
+    }
+
+    fn fetch_deposit_address(&self, _code: &str) -> DepositAddressResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Generate a new deposit address, the previous ones may stop being credited on some exchanges
+    /// 
+    fn create_deposit_address(&self, _code: &str) -> DepositAddressResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// The address is checked with check_address before anything is sent
+    /// 
+    fn withdraw(&self, _code: &str, _amount: f64, _address: &str, _tag: Option<&str>) -> WithdrawResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Deposits and withdrawals sorted by timestamp, code is required by some exchanges
+    /// 
+    fn fetch_transactions(&self, _code: Option<&str>, _since: Option<i64>, _limit: Option<i64>) -> FetchTransactionsResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Default to fetch_transactions filtered by type, the limit is applied after the filter
+    /// 
+    fn fetch_deposits(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTransactionsResult {
+        Box::new(self.fetch_transactions(code, since, None)
+            .map(move |transactions| filter_by_type_limit(transactions, TransactionType::Deposit, limit)))
+    }
+
+    fn fetch_withdrawals(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTransactionsResult {
+        Box::new(self.fetch_transactions(code, since, None)
+            .map(move |transactions| filter_by_type_limit(transactions, TransactionType::Withdrawal, limit)))
+    }
+
+    ///
+    /// Balance history sorted by timestamp, code is required by some exchanges
+    /// 
+    fn fetch_ledger(&self, _code: Option<&str>, _since: Option<i64>, _limit: Option<i64>) -> FetchLedgerResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Positions of the account, filtered by symbols when given
+    /// 
+    fn fetch_positions(&self, _symbols: Option<&[&str]>) -> FetchPositionsResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Set the leverage of the position on a market, return the updated position
+    /// 
+    fn set_leverage(&self, _symbol: &str, _leverage: f64) -> PositionResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Next funding rate of a swap
+    /// 
+    fn fetch_funding_rate(&self, _symbol: &str) -> FetchFundingRateResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    ///
+    /// Past funding rates sorted by timestamp
+    /// 
+    fn fetch_funding_history(&self, _symbol: Option<&str>, _since: Option<i64>, _limit: Option<i64>) -> FetchFundingHistoryResult {
+        Box::new(err(CCXTError::NotSupported.into()))
+    }
+
+    fn create_limit_order(&self, symbol: &str, side: OrderSide, amount: f64, price: f64) -> OrderResult {
+        self.create_order(symbol, OrderType::Limit, side, amount, Some(price))
+    }
+
+    fn create_market_order(&self, symbol: &str, side: OrderSide, amount: f64) -> OrderResult {
+        self.create_order(symbol, OrderType::Market, side, amount, None)
+    }
+
+    fn create_limit_buy_order(&self, symbol: &str, amount: f64, price: f64) -> OrderResult {
+        self.create_limit_order(symbol, OrderSide::Buy, amount, price)
+    }
+
+    fn create_limit_sell_order(&self, symbol: &str, amount: f64, price: f64) -> OrderResult {
+        self.create_limit_order(symbol, OrderSide::Sell, amount, price)
+    }
+
+    fn create_market_buy_order(&self, symbol: &str, amount: f64) -> OrderResult {
+        self.create_market_order(symbol, OrderSide::Buy, amount)
+    }
+
+    fn create_market_sell_order(&self, symbol: &str, amount: f64) -> OrderResult {
+        self.create_market_order(symbol, OrderSide::Sell, amount)
+    }
+}
+
+///
+/// Current timestamp in milliseconds
+/// 
+pub fn milliseconds() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+///
+/// Parse an iso8601 date (ex: 2018-10-18T12:00:00.000Z) to a timestamp in milliseconds
+/// 
+pub fn parse8601(date: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(date).ok().map(|date| date.timestamp_millis())
+}
+
+///
+/// Format a timestamp in milliseconds to an iso8601 date (ex: 2018-10-18T12:00:00.000Z)
+/// 
+pub fn iso8601(timestamp: i64) -> String {
+    let date = NaiveDateTime::from_timestamp(timestamp / 1000, (timestamp % 1000 * 1_000_000) as u32);
+    format!("{}", date.format("%Y-%m-%dT%H:%M:%S%.3fZ"))
+}
+
+///
+/// Same as filterBySinceLimit from the js base Exchange, entries without timestamp are dropped when since is given
+/// 
+pub fn filter_by_since_limit<T: Timestamped>(entries: Vec<T>, since: Option<i64>, limit: Option<i64>) -> Vec<T> {
+    entries.into_iter()
+        .filter(|entry| since.map_or(true, |since| entry.timestamp().map_or(false, |timestamp| timestamp >= since)))
+        .take(limit.map_or(usize::max_value(), |limit| limit.max(0) as usize))
+        .collect()
+}
+
+///
+/// Same as filterBySymbolSinceLimit from the js base Exchange
 /// 
-pub enum RequestResponse {
-    Error,
-    Success(Value),
+pub fn filter_by_symbol_since_limit<T: BySymbol>(entries: Vec<T>, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> Vec<T> {
+    let entries = entries.into_iter()
+        .filter(|entry| symbol.map_or(true, |symbol| entry.symbol() == symbol))
+        .collect();
+    filter_by_since_limit(entries, since, limit)
 }
 
-pub type ConnectorFuture<T> = Box<Future<Item=T, Error=Error> + Send>;
+///
+/// Same as filterByCurrencySinceLimit from the js base Exchange
+/// 
+pub fn filter_by_currency_since_limit<T: ByCurrency>(entries: Vec<T>, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> Vec<T> {
+    let entries = entries.into_iter()
+        .filter(|entry| code.map_or(true, |code| entry.currency() == code))
+        .collect();
+    filter_by_since_limit(entries, since, limit)
+}
 
-pub trait Connector {
-    fn request(&self, request: Request) -> ConnectorFuture<Value>;
+fn filter_by_type_limit(transactions: Vec<Transaction>, transaction_type: TransactionType, limit: Option<i64>) -> Vec<Transaction> {
+    let transactions = transactions.into_iter().filter(|transaction| transaction.transaction_type == transaction_type).collect();
+    filter_by_since_limit(transactions, None, limit)
+}
+
+fn filter_by_status_limit(orders: Vec<Order>, status: OrderStatus, limit: Option<i64>) -> Vec<Order> {
+    let orders = orders.into_iter().filter(|order| order.status == status).collect();
+    filter_by_since_limit(orders, None, limit)
 }
 
 ///
-/// @TODO
+/// Same as checkAddress from the js base Exchange, reject empty addresses, addresses with spaces and repeated characters
 /// 
-pub struct Credentials {}
-
+pub fn check_address(address: &str) -> Result<(), Error> {
+    let first = address.chars().next().ok_or(CCXTError::InvalidAddress)?;
+    if address.chars().all(|c| c == first) || address.contains(char::is_whitespace) {
+        return Err(CCXTError::InvalidAddress.into());
+    }
+    Ok(())
+}
 
 ///
-///
-/// The generic Exchange wrapper
+/// Address returned by an exchange, a missing or empty one mean that it's still being generated
 /// 
-/// 
-
-pub type CCXTFut<T> = Box<Future<Item=T, Error=Error> + Send>;
+pub fn parse_deposit_address(address: &Value) -> Result<String, Error> {
+    let address = address.as_str().filter(|address| !address.is_empty()).ok_or(CCXTError::AddressPending)?;
+    check_address(address)?;
+    Ok(String::from(address))
+}
 
-pub enum CCXTSymbol {
-    Undefined,
+///
+/// Same as precisionFromString from the js base Exchange, number of decimals of a step (ex: "0.00100000" -> 3)
+/// 
+pub fn precision_from_string(step: &str) -> f64 {
+    let step = step.trim_end_matches('0');
+    step.find('.').map_or(0, |dot| step.len() - dot - 1) as f64
 }
 
 ///
-/// amount (min, max)
-/// price (min, max)
-/// cost (min, max)
-#[derive(Debug, Clone)]
-pub struct MarketLimits {
-    pub amount: (f64, f64),
-    pub price: (f64, f64),
-    pub cost: (f64, f64),
+/// Same as decimalToPrecision with TRUNCATE from the js base Exchange, the decimals are cut on the string
+/// representation because flooring amount * 10^precision drops a unit when the product is not exact (ex: 1.15 -> 1.14)
+/// 
+pub fn truncate_to_precision(value: f64, precision: usize) -> String {
+    let value = value.to_string();
+    let mut parts = value.splitn(2, '.');
+    let integer = parts.next().unwrap_or("0");
+    let decimals = parts.next().unwrap_or("");
+    if precision == 0 {
+        return String::from(integer);
+    }
+    format!("{}.{:0<width$}", integer, &decimals[..decimals.len().min(precision)], width = precision)
 }
 
-impl MarketLimits {
-    pub fn new(amount: (f64, f64), price: (f64, f64), cost: (f64, f64)) -> Self {
-        MarketLimits {amount, price, cost}
+///
+/// Percent encode a query or form value (ex: a json filter)
+/// 
+pub fn url_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
     }
+    encoded
 }
 
-#[derive(Debug, Clone)]
-pub struct Market {
-    pub id: String,
-    pub symbol: String,
-    pub base_id: String,
-    pub quote_id: String,//Todo make enum referencin all symbols
-    pub active: bool,
-    pub precision: (f64,f64),
-    pub limits: MarketLimits,
-    pub info: Option<Value>,//Remove it if it's possible
+///
+/// Same as commonCurrencyCode from the js base Exchange, return the unified code of a currency id
+/// 
+pub fn common_currency_code(common_currencies: &HashMap<String, String>, currency: &str) -> String {
+    common_currencies.get(currency).cloned().unwrap_or_else(|| String::from(currency))
 }
 
-#[derive(Debug, Clone)]
-pub struct Ohlcv {
-    pub timestamp: i64,
-    pub open: f64,
-    pub highest: f64,
-    pub lowest: f64,
-    pub losing: f64,
-    pub volume: f64,
+///
+/// Market of an exchange id, markets are indexed by symbol
+/// 
+pub fn market_by_id<'a>(markets: &'a HashMap<String, Market>, id: &str) -> Option<&'a Market> {
+    markets.values().find(|market| market.id == id)
 }
 
-#[repr(u32)]
-pub enum CandleTime {
-    _1M=1,
-    _2M=2,
-    _3M=3,
-    _4M=4,
-    _5M=5,
-    _10M=10,
-    _15M=15,
-    _20M=20,
-    _30M=30,
-    _1H=60,
-    _2H=60*2,
-    _3H=60*3,
-    _4H=60*4,
-    _5H=60*5,
-    _1D=60*24,
-    _2D=60*24*2,
-    _3D=60*24*3,
-    _1W=60*24*7,
+///
+/// Unified symbol of a market id, fallback to the raw id for unknown markets
+/// 
+pub fn symbol_from_id(markets: &HashMap<String, Market>, id: &str) -> String {
+    market_by_id(markets, id).map_or_else(|| String::from(id), |market| market.symbol.clone())
 }
 
-pub type FetchOhlcvResult = CCXTFut<Vec<Ohlcv>>;
-pub type LoadMarketResult = CCXTFut<Arc<RwLock<Option<HashMap<String, Market>>>>>;
+///
+/// Parse a list of bids or asks, levels can be arrays (price_key = 0, amount_key = 1)
+/// or objects (price_key = "price", amount_key = "amount"), malformed levels are skipped
+/// 
+pub fn parse_bids_asks<I: Index + ?Sized>(bidasks: &Value, price_key: &I, amount_key: &I) -> Vec<(f64, f64)> {
+    bidasks.as_array().map(|bidasks| {
+        bidasks.iter().filter_map(|bidask| {
+            Some((safe_f64!(bidask[price_key])?, safe_f64!(bidask[amount_key])?))
+        }).collect()
+    }).unwrap_or_default()
+}
 
-pub trait ExchangeTrait {
-    fn fetch_ohlcv(&self, symbol: &str, timeframe: CandleTime, since: i64, limit: i64) -> FetchOhlcvResult;
-    fn fetch_markets(&mut self) -> LoadMarketResult;
-    //fn fetch_markets(&self);
-    //fn fetch_currencies(&self);
-    //fn fetch_ticker(&self);
-    //fn fetch_order_book(&self);
-    //fn fetch_treads(&self);
+///
+/// Same as parseOrderBook from the js base Exchange
+/// 
+pub fn parse_order_book<I: Index + ?Sized>(orderbook: &Value, timestamp: Option<i64>, bids_key: &str, asks_key: &str, price_key: &I, amount_key: &I) -> OrderBook {
+    OrderBook::new(
+        parse_bids_asks(&orderbook[bids_key], price_key, amount_key),
+        parse_bids_asks(&orderbook[asks_key], price_key, amount_key),
+        timestamp)
 }
 
 
@@ -263,15 +1394,24 @@ impl Display for ExchangeApiRoute {
     }
 }
 
+///
+/// costs are the rate limit weight of the routes that do not cost one token
+/// 
 #[derive(Debug, Clone)]
 pub struct ExchangeApi {
     get: Option<HashMap<String, ExchangeApiRoute>>,
     post: Option<HashMap<String, ExchangeApiRoute>>,
+    put: Option<HashMap<String, ExchangeApiRoute>>,
+    delete: Option<HashMap<String, ExchangeApiRoute>>,
+    costs: HashMap<(ApiMethod, String), f64>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ApiMethod {
     Get,
     Post,
+    Put,
+    Delete,
 }
 
 impl Default for ExchangeApi {
@@ -279,10 +1419,18 @@ impl Default for ExchangeApi {
         ExchangeApi {
             get: None,
             post: None,
+            put: None,
+            delete: None,
+            costs: HashMap::new(),
         }
     }
 }
 
+///
+/// Request timeout in milliseconds, same default as ccxt
+/// 
+pub const DEFAULT_TIMEOUT: u64 = 10000;
+
 #[derive(Debug, Clone)]
 pub struct Exchange<C: Connector + Debug + Clone> {
     connector: Option<Box<C>>,
@@ -295,8 +1443,19 @@ pub struct Exchange<C: Connector + Debug + Clone> {
     api: HashMap<String, ExchangeApi>,
     pub common_currencies: HashMap<String, String>,
     rate_limit: Option<u32>,
+    throttle: Option<Throttle>,
+    retry: RetryPolicy,
+    timeout: Duration,
     pub market: Arc<RwLock<Option<HashMap<String, Market>>>>,
     certified: bool,
+    credentials: Credentials,
+    required_credentials: Vec<String>,
+    sign: Option<SignHook<C>>,
+    exceptions: Arc<ExchangeExceptions>,
+    has: HashMap<String, bool>,
+    options: Value,
+    time_difference: Arc<RwLock<i64>>,
+    last_nonce: Arc<AtomicUsize>,
 }
 
 impl <C: Debug + Connector + Clone>Default for Exchange<C>  {
@@ -316,9 +1475,20 @@ impl <C: Debug + Connector + Clone>Default for Exchange<C>  {
             api_urls: HashMap::new(),
             market: Arc::new(RwLock::new(None)),
             api: HashMap::new(),
-            common_currencies: HashMap::new(),
+            common_currencies: currencies,
             rate_limit: None,
+            throttle: None,
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT),
             certified: false,
+            credentials: Credentials::default(),
+            required_credentials: vec![String::from("apiKey"), String::from("secret")],
+            sign: None,
+            exceptions: Arc::new(ExchangeExceptions::default()),
+            has: HashMap::new(),
+            options: Value::Null,
+            time_difference: Arc::new(RwLock::new(0)),
+            last_nonce: Arc::new(AtomicUsize::new(0)),
         }
     }
 }
@@ -327,9 +1497,11 @@ impl <T>Exchange<T> where T: Connector + Debug + Clone {
 
     fn parse_api_call(&self, api: &str, method: ApiMethod, route: &str, params: &[&str]) -> Result<Request, Error> {
         let api_def = self.api.get(api).ok_or(CCXTError::ApiUrlNotFound)?;
-        let method = match method {
+        let route_def = match method {
             ApiMethod::Get => &api_def.get,
-            ApiMethod::Post => &api_def.post,            
+            ApiMethod::Post => &api_def.post,
+            ApiMethod::Put => &api_def.put,
+            ApiMethod::Delete => &api_def.delete,
         }
             .as_ref()
             .ok_or(CCXTError::ApiUrlNotFound)?
@@ -337,13 +1509,19 @@ impl <T>Exchange<T> where T: Connector + Debug + Clone {
             .ok_or(CCXTError::ApiMethodNotFound)?;
 
         let api_url = self.api_urls.get(api).ok_or(CCXTError::ApiUrlNotFound)?;
-        let get_param_count = api_url.chars().filter(|c| *c == '{').count();
-        let url = format!("{}/{}", api_url, method.get_str(params)).parse()?;
-        let mut get = Vec::new();
-        for param in params[get_param_count..].iter() {
-            get.push(String::from(*param));
+        let get_param_count = route_def.to_string().chars().filter(|c| *c == '{').count();
+        let url = format!("{}/{}", api_url, route_def.get_str(params)).parse()?;
+        let mut query = Vec::new();
+        for param in params[get_param_count.min(params.len())..].iter() {
+            query.push(String::from(*param));
         }
-        Ok(Request::new(url, RequestMethod::Get(get)))
+        let body = |params: Vec<String>| if params.is_empty() { RequestBody::Empty } else { RequestBody::UrlEncoded(params) };
+        Ok(Request::new(url, match method {
+            ApiMethod::Get => RequestMethod::Get(query),
+            ApiMethod::Post => RequestMethod::Post(Vec::new(), body(query)),
+            ApiMethod::Put => RequestMethod::Put(Vec::new(), body(query)),
+            ApiMethod::Delete => RequestMethod::Delete(query, RequestBody::Empty),
+        }))
     }
 
 }
@@ -357,20 +1535,230 @@ impl <T: Debug + Connector + Clone> Exchange<T> {
         market.as_ref().unwrap().get(symbol).and_then(|elem|Some(elem.clone()))
     }
 
+    ///
+    /// Same as get_market_by_symbol but fail with an ExchangeError if markets are not loaded or the symbol is unknown
+    /// 
+    pub fn market(&self, symbol: &str) -> Result<Market, Error> {
+        let market = self.market.read().unwrap();
+        Ok(market.as_ref().ok_or(CCXTError::ExchangeError)?.get(symbol).ok_or(CCXTError::ExchangeError)?.clone())
+    }
+
+    pub fn get_market_by_id(&self, id: &str) -> Option<Market> {
+        let market = self.market.read().unwrap();
+        market.as_ref().and_then(|markets| market_by_id(markets, id).cloned())
+    }
+
     pub fn get_currencies(&self) -> &HashMap<String, String> {
         return &self.common_currencies;
     }
 
+    pub fn common_currency_code(&self, currency: &str) -> String {
+        common_currency_code(&self.common_currencies, currency)
+    }
+
+    ///
+    /// Exchange id of a unified currency code, taken from the base_id/quote_id of the loaded markets
+    /// common_currencies is not inverted as several ids can share a code (XBT and XXBT -> BTC)
+    /// 
+    pub fn currency_id(&self, code: &str) -> Option<String> {
+        let market = self.market.read().unwrap();
+        market.iter().flat_map(|markets| markets.values()).find_map(|market| {
+            if market.base == code {
+                Some(market.base_id.clone())
+            } else if market.quote == code {
+                Some(market.quote_id.clone())
+            } else {
+                None
+            }
+        })
+    }
+
+    ///
+    /// Build the currencies from the loaded markets, used by exchanges that do not have a currencies endpoint
+    /// 
+    pub fn currencies_from_markets(&self) -> HashMap<String, Currency> {
+        let mut currencies = HashMap::<String, Currency>::new();
+        let market = self.market.read().unwrap();
+        for market in market.iter().flat_map(|markets| markets.values()) {
+            for (id, code, precision) in vec![(&market.base_id, &market.base, market.precision.0), (&market.quote_id, &market.quote, market.precision.1)] {
+                currencies.entry(code.clone()).or_insert_with(|| Currency {
+                    id: id.clone(),
+                    code: code.clone(),
+                    active: true,
+                    precision: if precision > 0.0 { precision } else { 8.0 },
+                    deposit_fee: None,
+                    withdraw_fee: None,
+                    limits: CurrencyLimits::new((0.0, 0.0), (0.0, 0.0)),
+                    info: None,
+                });
+            }
+        }
+        currencies
+    }
+
+    ///
+    /// Send an api request, idempotent requests that fail with a transient error are retried following the retry policy
+    /// Each attempt is signed again so nonces and expiration dates stay valid
+    /// 
     pub fn call_api(&self, api: &str, method: ApiMethod, route: &str, params: &[&str]) -> ConnectorFuture<Value> {
-        let connector = try_future_box!(self.connector.as_ref().ok_or(CCXTError::Undefined));
-        let request = try_future_box!(self.parse_api_call(api, method, route, params));
-        Box::from(connector.request(request))
+        let cost = self.api.get(api).and_then(|api| api.costs.get(&(method, String::from(route)))).cloned().unwrap_or(1.0);
+        self.call_api_with_cost(api, method, route, params, cost)
+    }
+
+    ///
+    /// Same as call_api with the rate limit cost of this call, for routes whose weight depend on the parameters
+    /// 
+    pub fn call_api_with_cost(&self, api: &str, method: ApiMethod, route: &str, params: &[&str], cost: f64) -> ConnectorFuture<Value> {
+        if self.retry.max_attempts <= 1 || !RetryPolicy::is_idempotent(method) {
+            return self.send_api(api, method, route, params, cost);
+        }
+        let exchange = self.clone();
+        let api = String::from(api);
+        let route = String::from(route);
+        let params: Vec<String> = params.iter().map(|param| String::from(*param)).collect();
+        Box::new(loop_fn(1, move |attempt| {
+            let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+            let policy = exchange.retry.clone();
+            exchange.send_api(&api, method, &route, &params, cost).then(move |result| -> CCXTFut<Loop<Value, u32>> {
+                match result {
+                    Ok(value) => Box::new(ok(Loop::Break(value))),
+                    Err(error) => match policy.next_delay(attempt, &error) {
+                        Some(delay) => {
+                            if let (Some(on_retry), Some(kind)) = (policy.on_retry, error_kind(&error)) {
+                                on_retry(attempt, delay, kind);
+                            }
+                            Box::new(Delay::new(Instant::now() + delay)
+                                .map_err(|_| CCXTError::Undefined.into())
+                                .map(move |_| Loop::Continue(attempt + 1)))
+                        },
+                        None => Box::new(err(error)),
+                    },
+                }
+            })
+        }))
+    }
+
+    pub fn set_retry_policy(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    ///
+    /// Maximum duration of a single request (10 seconds by default)
+    /// 
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn send_api(&self, api: &str, method: ApiMethod, route: &str, params: &[&str], cost: f64) -> ConnectorFuture<Value> {
+        let connector = try_future_box!(self.connector.as_ref().ok_or(CCXTError::Undefined)).clone();
+        let mut request = try_future_box!(self.parse_api_call(api, method, route, params));
+        request.exceptions = Some(self.exceptions.clone());
+        request.timeout = Some(self.timeout);
+        match self.throttle.as_ref() {
+            Some(throttle) => {
+                //Sign once the wait is over, a nonce or an expiration date taken before would be stale
+                let exchange = self.clone();
+                let api = String::from(api);
+                Box::from(throttle.acquire(cost).and_then(move |_| {
+                    if let Some(sign) = exchange.sign {
+                        sign(&exchange, &api, &mut request)?;
+                    }
+                    Ok(request)
+                }).and_then(move |request| connector.request(request)))
+            },
+            None => {
+                if let Some(sign) = self.sign {
+                    try_future_box!(sign(self, api, &mut request));
+                }
+                Box::from(connector.request(request))
+            },
+        }
+    }
+
+    ///
+    /// Minimum delay between two requests in milliseconds, None disable the rate limiter
+    /// The new limiter is not shared with the previous clones of this exchange
+    /// 
+    pub fn set_rate_limit(&mut self, rate_limit: Option<u32>) {
+        self.rate_limit = rate_limit;
+        self.throttle = rate_limit.map(|rate_limit| Throttle::new(rate_limit, 1.0));
     }
 
     pub fn set_connector(&mut self, connector: Box<T>) {
         self.connector = Some(connector);
     }
 
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.credentials = credentials;
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    pub fn set_sign_hook(&mut self, sign: SignHook<T>) {
+        self.sign = Some(sign);
+    }
+
+    ///
+    /// Capabilities declared in the exchange "has" map (ex: "editOrder"), undeclared ones are false
+    /// 
+    pub fn has(&self, capability: &str) -> bool {
+        self.has.get(capability).cloned().unwrap_or(false)
+    }
+
+    pub fn exceptions(&self) -> Arc<ExchangeExceptions> {
+        self.exceptions.clone()
+    }
+
+    ///
+    /// Exchange specific settings of the json "options" object, Value::Null when missing
+    /// 
+    pub fn option(&self, key: &str) -> &Value {
+        &self.options[key]
+    }
+
+    ///
+    /// Milliseconds nonce adjusted to the exchange clock (see set_time_difference)
+    /// 
+    pub fn nonce(&self) -> i64 {
+        milliseconds() - *self.time_difference.read().unwrap()
+    }
+
+    ///
+    /// Same as nonce but always greater than the previous one, even for requests signed in the same millisecond
+    /// The last nonce is shared by the clones of the exchange
+    /// 
+    pub fn increasing_nonce(&self) -> i64 {
+        let mut last = self.last_nonce.load(atomic::Ordering::SeqCst);
+        loop {
+            let nonce = (self.nonce().max(0) as usize).max(last + 1);
+            match self.last_nonce.compare_exchange(last, nonce, atomic::Ordering::SeqCst, atomic::Ordering::SeqCst) {
+                Ok(_) => return nonce as i64,
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    ///
+    /// Difference between the local clock and the exchange one in milliseconds, shared by the clones of the exchange
+    /// 
+    pub fn set_time_difference(&self, difference: i64) {
+        *self.time_difference.write().unwrap() = difference;
+    }
+
+    ///
+    /// Fail with an AuthenticationError if one of the exchange required credentials is missing
+    /// 
+    pub fn check_required_credentials(&self) -> Result<(), Error> {
+        for name in self.required_credentials.iter() {
+            if self.credentials.get(name).map_or(true, |value| value.is_empty()) {
+                return Err(CCXTError::AuthenticationError.into());
+            }
+        }
+        Ok(())
+    }
+
     ///
     /// Ex json:
     /// {
@@ -411,10 +1799,31 @@ impl <T: Debug + Connector + Clone> Exchange<T> {
         for (key, api) in as_object!(settings["api"], "api")? {
             let mut newapi = ExchangeApi::default();
             for (route_key, routes) in as_object!(api, format!("api->{}", key))? {
+                let method = match route_key.as_ref() {
+                    "get" => ApiMethod::Get,
+                    "post" => ApiMethod::Post,
+                    "put" => ApiMethod::Put,
+                    "delete" => ApiMethod::Delete,
+                    _ => return Err(CCXTLoadingError::UndefinedField{field: format!("api->{}->{}", key, route_key)}.into()),
+                };
+                //Routes are either a list or an object with the rate limit cost of each route
+                let routes: Vec<(String, f64)> = match routes.as_object() {
+                    Some(routes) => routes.iter().map(|(route, cost)| (route.clone(), cost.as_f64().unwrap_or(1.0))).collect(),
+                    None => {
+                        let mut list = Vec::new();
+                        for route in as_array!(routes, "api->method->routes")? {
+                            list.push((String::from(as_str!(route, "api->method->routes->route")?), 1.0));
+                        }
+                        list
+                    },
+                };
                 let mut newroutes: HashMap<String, ExchangeApiRoute> = HashMap::new();
-                for route in as_array!(routes, "api->method->routes")? {
-                    let route = String::from(as_str!(route, "api->method->routes->route")?); 
-                    newroutes.insert(route.clone().replace("{", "").replace("}", ""), {
+                for (route, cost) in routes {
+                    let route_key = route.clone().replace("{", "").replace("}", "");
+                    if cost != 1.0 {
+                        newapi.costs.insert((method, route_key.clone()), cost);
+                    }
+                    newroutes.insert(route_key, {
                         if route.contains("{") {
                             ExchangeApiRoute::Formatable(route.clone())
                         } else {
@@ -423,15 +1832,59 @@ impl <T: Debug + Connector + Clone> Exchange<T> {
                     });
                 }
                 if newroutes.len() == 0 {continue;} 
-                match route_key.as_ref() {
-                    "get" => { newapi.get = Some(newroutes); },
-                    "post" => { newapi.get = Some(newroutes); },
-                    _ => { println!("Undefined api methode : {}", route_key) }
+                match method {
+                    ApiMethod::Get => { newapi.get = Some(newroutes); },
+                    ApiMethod::Post => { newapi.post = Some(newroutes); },
+                    ApiMethod::Put => { newapi.put = Some(newroutes); },
+                    ApiMethod::Delete => { newapi.delete = Some(newroutes); },
                 }
             }
             new_exchange.api.insert(String::from(key.as_ref()), newapi);
         }
 
+        //Load timeout (milliseconds)
+        if let Some(timeout) = settings["timeout"].as_u64() {
+            new_exchange.set_timeout(Duration::from_millis(timeout));
+        }
+
+        //Load rate limit (milliseconds between two requests)
+        if let Some(rate_limit) = settings["rateLimit"].as_u64() {
+            new_exchange.set_rate_limit(Some(rate_limit as u32));
+        }
+
+        //Load capabilities (ex: "has": {"editOrder": true})
+        if let Some(has) = settings["has"].as_object() {
+            for (capability, value) in has {
+                new_exchange.has.insert(capability.clone(), value.as_bool().unwrap_or(false));
+            }
+        }
+
+        new_exchange.options = settings["options"].clone();
+
+        //Load required credentials (apiKey and secret by default)
+        if let Some(required) = settings["requiredCredentials"].as_object() {
+            new_exchange.required_credentials = required.iter()
+                .filter(|(_, value)| value.as_bool().unwrap_or(false))
+                .map(|(key, _)| key.clone())
+                .collect();
+        }
+
+        //Load exceptions (ex: "exceptions": {"exact": {"Invalid API Key.": "AuthenticationError"}, "broad": {...}})
+        let mut exceptions = ExchangeExceptions::default();
+        if let Some(exact) = settings["exceptions"]["exact"].as_object() {
+            for (message, error) in exact {
+                exceptions.exact.insert(message.clone(), as_str!(error, "exceptions->exact")?.parse()?);
+            }
+        }
+        if let Some(broad) = settings["exceptions"]["broad"].as_object() {
+            for (message, error) in broad {
+                exceptions.broad.push((message.clone(), as_str!(error, "exceptions->broad")?.parse()?));
+            }
+        }
+        //Most specific broad messages first, json objects are not ordered
+        exceptions.broad.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        new_exchange.exceptions = Arc::new(exceptions);
+
         //Load copmmon currencies
         for (key, value) in as_object!(settings["commonCurrencies"], "commonCurrencies")? {
             new_exchange.common_currencies.insert(key.clone(), String::from(as_str!(value, "commonCurrencies->name")?));
@@ -439,4 +1892,230 @@ impl <T: Debug + Connector + Clone> Exchange<T> {
         Ok(new_exchange)
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(symbol: &str, timestamp: i64) -> Trade {
+        Trade {
+            id: None,
+            timestamp,
+            symbol: String::from(symbol),
+            order: None,
+            side: None,
+            taker_or_maker: None,
+            price: 1.0,
+            amount: 1.0,
+            cost: 1.0,
+            fee: None,
+            info: None,
+        }
+    }
+
+    //Reply with the time at which the request has been signed
+    #[derive(Debug, Clone)]
+    struct EchoConnector;
+
+    impl Connector for EchoConnector {
+        fn request(&self, request: Request) -> ConnectorFuture<Value> {
+            let signed_at = request.headers.iter().find(|(key, _)| key == "signed-at").map(|(_, value)| value.clone());
+            Box::new(ok(signed_at.and_then(|value| value.parse::<i64>().ok()).into()))
+        }
+    }
+
+    fn sign_now(_exchange: &Exchange<EchoConnector>, _api: &str, request: &mut Request) -> Result<(), Error> {
+        request.add_header("signed-at", &Utc::now().timestamp_millis().to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sign_after_throttle() {
+        let mut exchange = Exchange::<EchoConnector>::from_json::<EchoConnector>(r#"{
+            "id": "echo",
+            "api-urls": {"private": "https://echo.test"},
+            "api": {"private": {"get": ["order"]}},
+            "rateLimit": 300,
+            "commonCurrencies": {}
+        }"#).unwrap();
+        exchange.set_connector(Box::new(EchoConnector));
+        exchange.set_sign_hook(sign_now);
+        let first = exchange.call_api("private", ApiMethod::Get, "order", &[]);
+        let second = exchange.call_api("private", ApiMethod::Get, "order", &[]);
+        let start = Utc::now().timestamp_millis();
+        let (_, signed_at) = tokio::runtime::current_thread::block_on_all(first.join(second)).unwrap();
+        //The second request waited for the rate limit and has been signed after the wait
+        assert!(signed_at.as_i64().unwrap() >= start + 250);
+    }
+
+    //Record the order calls, the cancellation fails when the order is unknown
+    #[derive(Clone)]
+    struct StubExchange {
+        amend: bool,
+        calls: Arc<RwLock<Vec<String>>>,
+    }
+
+    impl StubExchange {
+        fn new(amend: bool) -> Self {
+            StubExchange { amend, calls: Arc::new(RwLock::new(Vec::new())) }
+        }
+
+        fn order(&self, call: &str, id: &str, price: Option<f64>) -> OrderResult {
+            self.calls.write().unwrap().push(format!("{} {}", call, id));
+            Box::new(ok(Order {
+                id: String::from(id),
+                client_order_id: None,
+                timestamp: None,
+                last_trade_timestamp: None,
+                symbol: String::from("BTC/USD"),
+                order_type: OrderType::Limit,
+                side: OrderSide::Buy,
+                price,
+                amount: 1.0,
+                filled: 0.0,
+                remaining: 1.0,
+                cost: 0.0,
+                status: OrderStatus::Open,
+                fee: None,
+                trades: Vec::new(),
+                info: None,
+            }))
+        }
+    }
+
+    impl ExchangeTrait for StubExchange {
+        fn fetch_ohlcv(&self, _symbol: &str, _timeframe: CandleTime, _since: i64, _limit: i64) -> FetchOhlcvResult {
+            Box::new(err(CCXTError::NotSupported.into()))
+        }
+
+        fn fetch_markets(&mut self) -> LoadMarketResult {
+            Box::new(err(CCXTError::NotSupported.into()))
+        }
+
+        fn has(&self, capability: &str) -> bool {
+            capability == "editOrder" && self.amend
+        }
+
+        fn amend_order(&self, id: &str, _symbol: &str, _amount: Option<f64>, price: Option<f64>) -> OrderResult {
+            self.order("amend", id, price)
+        }
+
+        fn cancel_order(&self, id: &str, _symbol: Option<&str>) -> OrderResult {
+            if id == "unknown" {
+                self.calls.write().unwrap().push(format!("cancel {}", id));
+                return Box::new(err(CCXTError::OrderNotFound.into()));
+            }
+            self.order("cancel", id, None)
+        }
+
+        fn create_order(&self, _symbol: &str, _order_type: OrderType, _side: OrderSide, _amount: f64, price: Option<f64>) -> OrderResult {
+            self.order("create", "new", price)
+        }
+    }
+
+    #[test]
+    fn test_edit_order() {
+        let exchange = StubExchange::new(true);
+        let edited = exchange.edit_order("a", "BTC/USD", OrderType::Limit, OrderSide::Buy, 1.0, Some(6500.0)).wait().unwrap();
+        assert_eq!(edited.method, EditMethod::Amend);
+        assert_eq!((edited.order.id.as_str(), edited.order.price), ("a", Some(6500.0)));
+        assert_eq!(*exchange.calls.read().unwrap(), vec!["amend a"]);
+
+        let exchange = StubExchange::new(false);
+        let edited = exchange.edit_order("a", "BTC/USD", OrderType::Limit, OrderSide::Buy, 1.0, Some(6500.0)).wait().unwrap();
+        assert_eq!(edited.method, EditMethod::CancelReplace);
+        assert_eq!((edited.order.id.as_str(), edited.order.price), ("new", Some(6500.0)));
+        assert_eq!(*exchange.calls.read().unwrap(), vec!["cancel a", "create new"]);
+
+        //No new order when the cancellation fails
+        let exchange = StubExchange::new(false);
+        let error = exchange.edit_order("unknown", "BTC/USD", OrderType::Limit, OrderSide::Buy, 1.0, Some(6500.0)).wait().unwrap_err();
+        assert_eq!(error_kind(&error), Some(CCXTError::OrderNotFound));
+        assert_eq!(*exchange.calls.read().unwrap(), vec!["cancel unknown"]);
+    }
+
+    #[test]
+    fn test_from_json_undefined_method() {
+        let exchange = Exchange::<EchoConnector>::from_json::<EchoConnector>(r#"{
+            "api": {"private": {"patch": ["order"]}},
+            "commonCurrencies": {}
+        }"#);
+        assert!(exchange.is_err());
+    }
+
+    #[test]
+    fn test_increasing_nonce() {
+        let exchange = Exchange::<EchoConnector>::default();
+        let clone = exchange.clone();
+        let first = exchange.increasing_nonce();
+        assert!(first >= milliseconds() - 1000);
+        assert!(clone.increasing_nonce() > first);
+        //A clock set back does not make the nonce go backward
+        exchange.set_time_difference(60000);
+        assert!(exchange.increasing_nonce() > first + 1);
+    }
+
+    #[test]
+    fn test_precision_from_string() {
+        assert_eq!(precision_from_string("0.00100000"), 3.0);
+        assert_eq!(precision_from_string("1.00000000"), 0.0);
+        assert_eq!(precision_from_string("10"), 0.0);
+    }
+
+    #[test]
+    fn test_truncate_to_precision() {
+        assert_eq!(truncate_to_precision(1.15, 2), "1.15");
+        assert_eq!(truncate_to_precision(0.29, 2), "0.29");
+        assert_eq!(truncate_to_precision(4.35, 2), "4.35");
+        assert_eq!(truncate_to_precision(1.159, 2), "1.15");
+        assert_eq!(truncate_to_precision(2.0, 3), "2.000");
+        assert_eq!(truncate_to_precision(2.75, 0), "2");
+    }
+
+    #[test]
+    fn test_check_address() {
+        assert!(check_address("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").is_ok());
+        assert!(check_address("").is_err());
+        assert!(check_address("aaaaa").is_err());
+        assert!(check_address("1BvBMSEY stWetq").is_err());
+        assert_eq!(error_kind(&parse_deposit_address(&Value::Null).unwrap_err()), Some(CCXTError::AddressPending));
+    }
+
+    #[test]
+    fn test_currency_id() {
+        let exchange = Exchange::<crate::base::http_connector::HttpConnector>::default();
+        let market = Market {
+            id: String::from("XXBTZUSD"),
+            symbol: String::from("BTC/USD"),
+            base: String::from("BTC"),
+            quote: String::from("USD"),
+            base_id: String::from("XXBT"),
+            quote_id: String::from("ZUSD"),
+            active: true,
+            precision: (8.0, 1.0),
+            limits: MarketLimits::new((0.0, 0.0), (0.0, 0.0), (0.0, 0.0)),
+            market_type: MarketType::Spot,
+            linear: None,
+            settle: None,
+            contract_size: None,
+            expiry: None,
+            underlying_index: None,
+            info: None,
+        };
+        *exchange.market.write().unwrap() = Some(vec![(market.symbol.clone(), market)].into_iter().collect());
+        assert_eq!(exchange.currency_id("BTC"), Some(String::from("XXBT")));
+        assert_eq!(exchange.currency_id("USD"), Some(String::from("ZUSD")));
+        assert_eq!(exchange.currency_id("DASH"), None);
+    }
+
+    #[test]
+    fn test_filter_by_symbol_since_limit() {
+        let trades = vec![trade("BTC/USD", 1), trade("ETH/USD", 2), trade("BTC/USD", 3), trade("BTC/USD", 4)];
+        let filtered = filter_by_symbol_since_limit(trades.clone(), Some("BTC/USD"), Some(2), None);
+        assert_eq!(filtered.iter().map(|trade| trade.timestamp).collect::<Vec<_>>(), vec![3, 4]);
+        let filtered = filter_by_symbol_since_limit(trades.clone(), None, None, Some(2));
+        assert_eq!(filtered.iter().map(|trade| trade.timestamp).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(filter_by_since_limit(trades, Some(5), None).len(), 0);
+    }
+}
@@ -9,6 +9,8 @@ use hyper::rt::{Future, Stream};
 use hyper::client::HttpConnector as HyperHttpConnector;
 use hyper_tls::HttpsConnector;
 use serde_json::Value;
+use std::time::Duration;
+use tokio::timer::Timeout;
 
 #[derive(Debug, Clone)]
 pub struct HttpConnector {
@@ -32,32 +34,57 @@ impl From<CCXTError> for Box<Error> {
 
 impl Connector for HttpConnector {
     fn request(&self, request: Request) -> ConnectorFuture<Value> {
-        Box::new(match request.method {
-            RequestMethod::Get(params) => {
-                let mut concat = format!("{}?", request.path);
-                for (index, param) in params.iter().enumerate() {
-                    if index == params.len() - 1 {
-                        concat.push_str(param);
-                    } else {
-                        concat.push_str(format!("{}&", param).as_str());
+        let url = request.url();
+        let mut builder = hyper::Request::builder();
+        builder.method(request.method_name()).uri(url.as_str());
+        let body = request.body();
+        if let Some(content_type) = body.content_type() {
+            if !request.headers.iter().any(|(key, _)| key.to_lowercase() == "content-type") {
+                builder.header("Content-Type", content_type);
+            }
+        }
+        for (key, value) in request.headers.iter() {
+            builder.header(key.as_str(), value.as_str());
+        }
+        let http_request = try_future_box!(builder.body(hyper::Body::from(body.encode())).map_err(|_| CCXTError::ApiUrlMalformated));
+        let exceptions = request.exceptions.clone();
+        let response = self.client
+                .request(http_request)
+                .and_then(|res| {
+                    let status = res.status().as_u16();
+                    let retry_after = res.headers().get("Retry-After")
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    res.into_body().concat2().map(move |body| (status, retry_after, body))
+                })
+                .map_err(|_| CCXTError::NetworkError.into())
+                .and_then(move |(status, retry_after, body)| -> Result<Value, Error> {
+                    let json = serde_json::from_slice::<Value>(&body);
+                    if let (Some(exceptions), Ok(json)) = (exceptions.as_ref(), json.as_ref()) {
+                        if let Some(kind) = exceptions.find_in_response(json) {
+                            let mut error = CCXTHttpError::with_kind(kind, status, String::from_utf8_lossy(&body).into_owned());
+                            error.retry_after = retry_after;
+                            return Err(error.into());
+                        }
+                    }
+                    if status < 200 || status > 299 {
+                        let mut error = CCXTHttpError::new(status, String::from_utf8_lossy(&body).into_owned());
+                        error.retry_after = retry_after;
+                        return Err(error.into());
                     }
+                    json.map_err(|_| CCXTError::BadResponse.into())
+                });
+        //Nothing is spawned, dropping the returned future drop the hyper response future and abort the request
+        match request.timeout {
+            Some(timeout) => Box::new(Timeout::new(response, timeout).map_err(|e| {
+                if e.is_elapsed() {
+                    CCXTError::RequestTimeout.into()
+                } else {
+                    e.into_inner().unwrap_or_else(|| CCXTError::Undefined.into())
                 }
-                println!("@@ Send -> {:?}", concat);
-                let concat = concat.parse();//IMPORTRTANT NOT UNWRAP
-                self.client
-                        .get(concat.unwrap_or_default())
-                        .and_then(|res| res.into_body().concat2())
-                        .map_err(|e| {
-                            println!("@@ Send error : {}", e);
-                            CCXTError::Undefined
-                        })
-                        .and_then(|body| Ok(serde_json::from_slice(&body)?))
-                        .map_err(|e| {
-                            println!("@@ Send error : {}", e);
-                            CCXTError::ApiUrlMalformated.into()
-                        })
-            }
-            _ => unimplemented!()
-        })
+            })),
+            None => Box::new(response),
+        }
     }
-}
\ No newline at end of file
+}
@@ -2,6 +2,9 @@ pub mod errors;
 #[macro_use]
 pub mod exchange;
 pub mod http_connector;
+pub mod crypto;
+pub mod throttle;
+pub mod retry;
 
 pub use self::errors::*;
 pub use self::exchange::*;
\ No newline at end of file
@@ -0,0 +1,99 @@
+//!
+//! Retry policy applied by Exchange::call_api on transient errors
+//! 
+use super::errors::*;
+use super::exchange::ApiMethod;
+use failure::Error;
+use rand::Rng;
+use std::time::Duration;
+
+///
+/// Requests are sent at most max_attempts times, the delay between two attempts is
+/// base_delay * 2^(attempt - 1) capped to max_delay, with a random jitter of +/- jitter (0.25 = 25%)
+/// A Retry-After header sent by the exchange is used instead of the backoff when it is longer
+/// on_retry is called with the failed attempt, the delay and the error kind before each retry (ex: to log them)
+/// 
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: f64,
+    pub on_retry: Option<fn(u32, Duration, CCXTError)>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.25,
+            on_retry: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    ///
+    /// Never retry
+    /// 
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {max_attempts: 1, ..RetryPolicy::default()}
+    }
+
+    ///
+    /// Only idempotent requests are retried, a POST may already have been executed by the exchange
+    /// 
+    pub fn is_idempotent(method: ApiMethod) -> bool {
+        method != ApiMethod::Post
+    }
+
+    pub fn is_transient(error: &Error) -> bool {
+        match error_kind(error) {
+            Some(CCXTError::NetworkError) | Some(CCXTError::RequestTimeout) | Some(CCXTError::DDoSProtection) => true,
+            _ => false,
+        }
+    }
+
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let millis = |duration: Duration| duration.as_secs() as f64 * 1000.0 + f64::from(duration.subsec_millis());
+        let delay = (millis(self.base_delay) * 2f64.powi(attempt.saturating_sub(1) as i32)).min(millis(self.max_delay));
+        let jitter = if self.jitter > 0.0 { rand::thread_rng().gen_range(-self.jitter, self.jitter) } else { 0.0 };
+        Duration::from_millis((delay * (1.0 + jitter)).max(0.0) as u64)
+    }
+
+    ///
+    /// Delay before the next attempt, or None if the error must be returned to the caller
+    /// attempt is the number of the attempt that failed (starting at 1)
+    /// 
+    pub fn next_delay(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt >= self.max_attempts || !Self::is_transient(error) {
+            return None;
+        }
+        let backoff = self.backoff(attempt);
+        let retry_after = error.downcast_ref::<CCXTHttpError>().and_then(|error| error.retry_after);
+        Some(retry_after.map_or(backoff, |retry_after| retry_after.max(backoff)))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_delay() {
+        let policy = RetryPolicy {jitter: 0.0, ..RetryPolicy::default()};
+        let timeout: Error = CCXTError::RequestTimeout.into();
+        assert_eq!(policy.next_delay(1, &timeout), Some(Duration::from_millis(500)));
+        assert_eq!(policy.next_delay(2, &timeout), Some(Duration::from_millis(1000)));
+        assert_eq!(policy.next_delay(3, &timeout), None);
+        let auth: Error = CCXTError::AuthenticationError.into();
+        assert_eq!(policy.next_delay(1, &auth), None);
+        let mut rate_limited = CCXTHttpError::new(429, String::new());
+        rate_limited.retry_after = Some(Duration::from_secs(5));
+        assert_eq!(policy.next_delay(1, &rate_limited.into()), Some(Duration::from_secs(5)));
+        assert!(!RetryPolicy::is_idempotent(ApiMethod::Post));
+    }
+}
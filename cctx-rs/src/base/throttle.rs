@@ -0,0 +1,83 @@
+//!
+//! Token bucket rate limiter, same behaviour as the throttle used by initRestRateLimiter in the js base Exchange
+//! 
+use super::errors::*;
+use failure::Error;
+use futures::Future;
+use futures::future::ok;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::timer::Delay;
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    capacity: f64,
+    refill_rate: f64,
+    last: Instant,
+}
+
+///
+/// Every request consume `cost` tokens, the bucket is refilled with one token every `rate_limit` milliseconds
+/// A request that find the bucket empty reserve its tokens anyway and wait until they are refilled
+/// so concurrent requests are queued in the order they called acquire
+/// Clones share the same bucket
+/// 
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    bucket: Arc<Mutex<TokenBucket>>,
+}
+
+impl Throttle {
+    pub fn new(rate_limit: u32, capacity: f64) -> Self {
+        Throttle {
+            bucket: Arc::new(Mutex::new(TokenBucket {
+                tokens: capacity,
+                capacity,
+                refill_rate: 1.0 / f64::from(rate_limit.max(1)),
+                last: Instant::now(),
+            })),
+        }
+    }
+
+    ///
+    /// Time to wait before a request of the given cost can be sent, the tokens are reserved
+    /// 
+    pub fn reserve(&self, cost: f64) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last);
+        let elapsed = elapsed.as_secs() as f64 * 1000.0 + f64::from(elapsed.subsec_millis());
+        bucket.tokens = (bucket.tokens + elapsed * bucket.refill_rate).min(bucket.capacity);
+        bucket.last = now;
+        bucket.tokens -= cost;
+        if bucket.tokens >= 0.0 {
+            Duration::from_millis(0)
+        } else {
+            Duration::from_millis((-bucket.tokens / bucket.refill_rate).ceil() as u64)
+        }
+    }
+
+    pub fn acquire(&self, cost: f64) -> Box<Future<Item=(), Error=Error> + Send> {
+        let delay = self.reserve(cost);
+        if delay == Duration::from_millis(0) {
+            return Box::new(ok(()));
+        }
+        Box::new(Delay::new(Instant::now() + delay).map_err(|_| CCXTError::Undefined.into()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Throttle;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reserve() {
+        let throttle = Throttle::new(1000, 1.0);
+        assert_eq!(throttle.reserve(1.0), Duration::from_millis(0));
+        assert!(throttle.clone().reserve(1.0) > Duration::from_millis(900));
+        assert!(throttle.reserve(1.0) > Duration::from_millis(1900));
+    }
+}
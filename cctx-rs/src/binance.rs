@@ -0,0 +1,768 @@
+use std::sync::Once;
+use super::prelude::*;
+use super::base::crypto::hmac_sha256_hex;
+use std::collections::HashMap;
+use futures::Future;
+use futures::future::{ok, err};
+use serde_json::Value;
+
+static INIT: Once = Once::new();
+static mut BINANCE_EXCHANGE: Option<Exchange<HttpConnector>> = None;
+//Weight of ticker/24hr and openOrders when they are called for all the markets
+const NO_SYMBOL_COST: f64 = 40.0;
+
+///
+/// Route costs are the Binance request weights, the limit is 1200 weight per minute so one token is refilled every 50ms
+/// Orders cost 2 to stay under 10 orders per second
+///
+#[derive(Clone)]
+pub struct Binance {
+    exchange: Exchange<HttpConnector>,
+}
+
+impl Binance {
+
+    pub fn new() -> CCXTFut<Self> {
+        INIT.call_once(||{
+            unsafe {
+                BINANCE_EXCHANGE = Some(Exchange::<HttpConnector>::from_json(r#"
+                {
+                    "id": "binance",
+                    "name": "Binance",
+                    "rateLimit": 50,
+                    "has": {
+                        "fetchOHLCV": true,
+                        "fetchTickers": true,
+                        "fetchMyTrades": true,
+                        "fetchOrder": true,
+                        "fetchOrders": true,
+                        "fetchOpenOrders": true,
+                        "fetchClosedOrders": true
+                    },
+                    "options": {
+                        "recvWindow": 5000,
+                        "defaultTimeInForce": "GTC",
+                        "adjustForTimeDifference": true
+                    },
+                    "api-urls": {
+                        "public": "https://api.binance.com/api/v1",
+                        "private": "https://api.binance.com/api/v3"
+                    },
+                    "api": {
+                        "public": {
+                            "get": {
+                                "exchangeInfo": 1,
+                                "ping": 1,
+                                "time": 1,
+                                "depth": 1,
+                                "aggTrades": 1,
+                                "klines": 1,
+                                "ticker/24hr": 1,
+                                "ticker/price": 1,
+                                "ticker/bookTicker": 1
+                            }
+                        },
+                        "private": {
+                            "get": {
+                                "order": 1,
+                                "openOrders": 1,
+                                "allOrders": 5,
+                                "account": 5,
+                                "myTrades": 5
+                            },
+                            "post": {
+                                "order": 2,
+                                "order/test": 1
+                            },
+                            "delete": {
+                                "order": 1
+                            }
+                        }
+                    },
+                    "exceptions": {
+                        "exact": {
+                            "-1000": "ExchangeNotAvailable",
+                            "-1013": "InvalidOrder",
+                            "-1021": "InvalidNonce",
+                            "-1022": "AuthenticationError",
+                            "-1100": "InvalidOrder",
+                            "-1104": "ExchangeError",
+                            "-1128": "ExchangeError",
+                            "-2010": "ExchangeError",
+                            "-2011": "OrderNotFound",
+                            "-2013": "OrderNotFound",
+                            "-2014": "AuthenticationError",
+                            "-2015": "AuthenticationError",
+                            "Order would trigger immediately.": "InvalidOrder",
+                            "Account has insufficient balance for requested action.": "InsufficientFunds",
+                            "Rest API trading is not enabled.": "ExchangeNotAvailable"
+                        },
+                        "broad": {
+                            "Price * QTY is zero or less": "InvalidOrder",
+                            "LOT_SIZE": "InvalidOrder",
+                            "PRICE_FILTER": "InvalidOrder"
+                        }
+                    },
+                    "commonCurrencies": {
+                        "YOYO": "YOYOW",
+                        "BCC": "BCH"
+                    }
+                }
+            "#).unwrap())
+            }
+        });
+        let connector = HttpConnector::new();
+        let mut exchange = unsafe {BINANCE_EXCHANGE.as_ref().unwrap().clone()};
+        exchange.set_connector(Box::new(connector));
+        exchange.set_sign_hook(Self::sign);
+        let adjust = exchange.option("adjustForTimeDifference").as_bool().unwrap_or(false);
+        let mut exchange = Binance { exchange };
+        Box::from(exchange.fetch_markets().and_then(move |_| -> CCXTFut<Self> {
+            if adjust {
+                let time_difference = exchange.load_time_difference();
+                Box::new(time_difference.map(move |_| exchange))
+            } else {
+                Box::new(ok(exchange))
+            }
+        }))
+    }
+
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.exchange.set_credentials(credentials);
+    }
+
+    ///
+    /// Synchronise the nonce with the server time, signed requests are rejected (-1021)
+    /// when their timestamp is outside of the recvWindow
+    ///
+    pub fn load_time_difference(&self) -> CCXTFut<i64> {
+        let exchange = self.exchange.clone();
+        Box::from(get_api!(self.exchange, "public", "time")
+            .and_then(move |json| {
+                let difference = milliseconds() - as_i64!(json["serverTime"], "time->serverTime")?;
+                exchange.set_time_difference(difference);
+                Ok(difference)
+            }))
+    }
+
+    ///
+    /// Private requests are signed with HMAC-SHA256(secret, params + recvWindow + timestamp)
+    /// See https://github.com/binance-exchange/binance-official-api-docs/blob/master/rest-api.md#signed-trade-and-user_data-endpoint-security
+    ///
+    fn sign(exchange: &Exchange<HttpConnector>, api: &str, request: &mut Request) -> Result<(), Error> {
+        if api != "private" {
+            return Ok(());
+        }
+        exchange.check_required_credentials()?;
+        let credentials = exchange.credentials();
+        let api_key = credentials.api_key.clone().unwrap_or_default();
+        let secret = credentials.secret.clone().unwrap_or_default();
+        let recv_window = exchange.option("recvWindow").as_i64().unwrap_or(5000);
+        Self::sign_request(request, &api_key, &secret, exchange.nonce(), recv_window);
+        Ok(())
+    }
+
+    fn sign_request(request: &mut Request, api_key: &str, secret: &str, timestamp: i64, recv_window: i64) {
+        request.push_param(format!("recvWindow={}", recv_window));
+        request.push_param(format!("timestamp={}", timestamp));
+        let signature = hmac_sha256_hex(secret.as_bytes(), request.encoded_params().as_bytes());
+        request.push_param(format!("signature={}", signature));
+        request.add_header("X-MBX-APIKEY", api_key);
+    }
+
+    fn time_frame(time: CandleTime) -> Option<&'static str> {
+        match time {
+            CandleTime::_1M => Some("1m"),
+            CandleTime::_3M => Some("3m"),
+            CandleTime::_5M => Some("5m"),
+            CandleTime::_15M => Some("15m"),
+            CandleTime::_30M => Some("30m"),
+            CandleTime::_1H => Some("1h"),
+            CandleTime::_2H => Some("2h"),
+            CandleTime::_4H => Some("4h"),
+            CandleTime::_6H => Some("6h"),
+            CandleTime::_8H => Some("8h"),
+            CandleTime::_12H => Some("12h"),
+            CandleTime::_1D => Some("1d"),
+            CandleTime::_3D => Some("3d"),
+            CandleTime::_1W => Some("1w"),
+            CandleTime::_1MONTH => Some("1M"),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Amounts are truncated to the market precision, prices are rounded
+    ///
+    ///
+    /// Weight of a depth request, it grows with the limit (default 100)
+    ///
+    fn depth_cost(limit: Option<i64>) -> f64 {
+        match limit.unwrap_or(100) {
+            limit if limit <= 100 => 1.0,
+            limit if limit <= 500 => 5.0,
+            limit if limit <= 1000 => 10.0,
+            _ => 50.0,
+        }
+    }
+
+    fn price_to_precision(market: &Market, price: f64) -> String {
+        format!("{:.*}", market.precision.1 as usize, price)
+    }
+
+    ///
+    /// Klines are arrays of [open time (ms), open, high, low, close, volume, ...], timestamps are returned in seconds
+    ///
+    fn parse_ohlcv(json: &Value) -> Result<Vec<Ohlcv>, Error> {
+        let mut ohlcv = Vec::<Ohlcv>::new();
+        for kline in as_array!(json, "ohlcv")? {
+            ohlcv.push(Ohlcv {
+                timestamp: as_i64!(kline[0], "ohlcv->timestamp")? / 1000,
+                open: safe_f64!(kline[1]).ok_or(CCXTError::BadResponse)?,
+                highest: safe_f64!(kline[2]).ok_or(CCXTError::BadResponse)?,
+                lowest: safe_f64!(kline[3]).ok_or(CCXTError::BadResponse)?,
+                losing: safe_f64!(kline[4]).ok_or(CCXTError::BadResponse)?,
+                volume: safe_f64!(kline[5]).ok_or(CCXTError::BadResponse)?,
+            });
+        }
+        Ok(ohlcv)
+    }
+
+    ///
+    /// Limits and precisions come from the PRICE_FILTER, LOT_SIZE and MIN_NOTIONAL filters
+    ///
+    fn parse_markets(re: Value, currencies: &HashMap<String, String>) -> Result<HashMap<String, Market>, Error> {
+        let mut markets = HashMap::<String, Market>::new();
+        for market in as_array!(re["symbols"], "markets")? {
+            let id = String::from(as_str!(market["symbol"], "market->symbol")?);
+            //Test market
+            if id == "123456" {
+                continue;
+            }
+            let base_id = String::from(as_str!(market["baseAsset"], "market->baseAsset")?);
+            let quote_id = String::from(as_str!(market["quoteAsset"], "market->quoteAsset")?);
+            let base = common_currency_code(currencies, &base_id);
+            let quote = common_currency_code(currencies, &quote_id);
+            let symbol = format!("{}/{}", base, quote);
+            let mut precision = (as_i64_or!(market["baseAssetPrecision"], 8) as f64, as_i64_or!(market["quotePrecision"], 8) as f64);
+            let mut limits = MarketLimits::new((0.0, 0.0), (0.0, 0.0), (0.0, 0.0));
+            for filter in as_array!(market["filters"], "market->filters")? {
+                match filter["filterType"].as_str() {
+                    Some("PRICE_FILTER") => {
+                        precision.1 = filter["tickSize"].as_str().map_or(precision.1, precision_from_string);
+                        limits.price = (safe_f64!(filter["minPrice"]).unwrap_or(0.0), safe_f64!(filter["maxPrice"]).unwrap_or(0.0));
+                    },
+                    Some("LOT_SIZE") => {
+                        precision.0 = filter["stepSize"].as_str().map_or(precision.0, precision_from_string);
+                        limits.amount = (safe_f64!(filter["minQty"]).unwrap_or(0.0), safe_f64!(filter["maxQty"]).unwrap_or(0.0));
+                    },
+                    Some("MIN_NOTIONAL") => {
+                        limits.cost.0 = safe_f64!(filter["minNotional"]).unwrap_or(0.0);
+                    },
+                    _ => {},
+                }
+            }
+            markets.insert(symbol.clone(), Market {
+                id,
+                symbol,
+                base,
+                quote,
+                base_id,
+                quote_id,
+                active: market["status"].as_str() == Some("TRADING"),
+                precision,
+                limits,
+                market_type: MarketType::Spot,
+                linear: None,
+                settle: None,
+                contract_size: None,
+                expiry: None,
+                underlying_index: None,
+                info: None,
+            });
+        }
+        Ok(markets)
+    }
+
+    fn parse_ticker(ticker: &Value, symbol: String) -> Ticker {
+        let last = safe_f64!(ticker["lastPrice"]);
+        Ticker {
+            symbol,
+            timestamp: ticker["closeTime"].as_i64().unwrap_or_else(milliseconds),
+            high: safe_f64!(ticker["highPrice"]),
+            low: safe_f64!(ticker["lowPrice"]),
+            bid: safe_f64!(ticker["bidPrice"]),
+            bid_volume: safe_f64!(ticker["bidQty"]),
+            ask: safe_f64!(ticker["askPrice"]),
+            ask_volume: safe_f64!(ticker["askQty"]),
+            vwap: safe_f64!(ticker["weightedAvgPrice"]),
+            open: safe_f64!(ticker["openPrice"]),
+            close: last,
+            last,
+            previous_close: safe_f64!(ticker["prevClosePrice"]),
+            change: safe_f64!(ticker["priceChange"]),
+            percentage: safe_f64!(ticker["priceChangePercent"]),
+            average: None,
+            base_volume: safe_f64!(ticker["volume"]),
+            quote_volume: safe_f64!(ticker["quoteVolume"]),
+            info: Some(ticker.clone()),
+        }
+    }
+
+    ///
+    /// Parse an aggregated trade (aggTrades) or an account trade (myTrades)
+    /// The side of aggregated trades is the taker one, m is true when the buyer is the maker
+    ///
+    fn parse_trade(trade: &Value, symbol: String, currencies: &HashMap<String, String>) -> Result<Trade, Error> {
+        let aggregated = trade.get("a").is_some();
+        let (id, timestamp, price, amount) = if aggregated {
+            (&trade["a"], &trade["T"], &trade["p"], &trade["q"])
+        } else {
+            (&trade["id"], &trade["time"], &trade["price"], &trade["qty"])
+        };
+        let price = safe_f64!(price).ok_or(CCXTError::BadResponse)?;
+        let amount = safe_f64!(amount).ok_or(CCXTError::BadResponse)?;
+        let side = match (trade["m"].as_bool(), trade["isBuyer"].as_bool()) {
+            (Some(buyer_maker), _) => Some(if buyer_maker { OrderSide::Sell } else { OrderSide::Buy }),
+            (None, Some(buyer)) => Some(if buyer { OrderSide::Buy } else { OrderSide::Sell }),
+            _ => None,
+        };
+        let fee = safe_f64!(trade["commission"]).map(|cost| Fee {
+            cost,
+            currency: trade["commissionAsset"].as_str().map(|currency| common_currency_code(currencies, currency)),
+            rate: None,
+        });
+        Ok(Trade {
+            id: safe_string!(id),
+            timestamp: as_i64!(timestamp, "trade->timestamp")?,
+            symbol,
+            order: safe_string!(trade["orderId"]),
+            side,
+            taker_or_maker: trade["isMaker"].as_bool().map(|maker| if maker { TakerOrMaker::Maker } else { TakerOrMaker::Taker }),
+            price,
+            amount,
+            cost: price * amount,
+            fee,
+            info: Some(trade.clone()),
+        })
+    }
+
+    fn parse_order_status(status: &str) -> Result<OrderStatus, Error> {
+        match status {
+            "NEW" | "PARTIALLY_FILLED" => Ok(OrderStatus::Open),
+            "FILLED" => Ok(OrderStatus::Closed),
+            "CANCELED" | "PENDING_CANCEL" => Ok(OrderStatus::Canceled),
+            "REJECTED" => Ok(OrderStatus::Rejected),
+            "EXPIRED" => Ok(OrderStatus::Expired),
+            _ => Err(CCXTError::BadResponse.into()),
+        }
+    }
+
+    ///
+    /// Parse an order, the price of filled market orders is the average price
+    ///
+    fn parse_order(order: &Value, markets: &HashMap<String, Market>) -> Result<Order, Error> {
+        let symbol = symbol_from_id(markets, as_str!(order["symbol"], "order->symbol")?);
+        let order_type: OrderType = as_str!(order["type"], "order->type")?.parse()?;
+        let amount = safe_f64!(order["origQty"]).unwrap_or(0.0);
+        let filled = safe_f64!(order["executedQty"]).unwrap_or(0.0);
+        let mut price = safe_f64!(order["price"]);
+        let cost = safe_f64!(order["cummulativeQuoteQty"]).unwrap_or_else(|| price.map_or(0.0, |price| price * filled));
+        if order_type == OrderType::Market && price == Some(0.0) && filled > 0.0 {
+            price = Some(cost / filled);
+        }
+        Ok(Order {
+            id: safe_string!(order["orderId"]).ok_or(CCXTError::BadResponse)?,
+            client_order_id: safe_string!(order["clientOrderId"]),
+            timestamp: order["time"].as_i64().or_else(|| order["transactTime"].as_i64()),
+            last_trade_timestamp: order["updateTime"].as_i64(),
+            symbol,
+            order_type,
+            side: as_str!(order["side"], "order->side")?.parse()?,
+            price,
+            amount,
+            filled,
+            remaining: (amount - filled).max(0.0),
+            cost,
+            status: Self::parse_order_status(as_str!(order["status"], "order->status")?)?,
+            fee: None,
+            trades: Vec::new(),
+            info: Some(order.clone()),
+        })
+    }
+
+    fn parse_orders(json: Value, markets: &HashMap<String, Market>, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> Result<Vec<Order>, Error> {
+        let mut orders = Vec::new();
+        for order in as_array!(json, "orders")? {
+            orders.push(Self::parse_order(order, markets)?);
+        }
+        orders.sort_by_key(|order| order.timestamp);
+        Ok(filter_by_symbol_since_limit(orders, symbol, since, limit))
+    }
+
+}
+
+impl ExchangeTrait for Binance {
+
+    fn has(&self, capability: &str) -> bool {
+        self.exchange.has(capability)
+    }
+
+    ///
+    /// since is in seconds like the returned timestamps
+    ///
+    fn fetch_ohlcv(&self, symbol: &str, timeframe: CandleTime, since: i64, limit: i64) -> FetchOhlcvResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let interval = try_future_box!(Self::time_frame(timeframe).ok_or(CCXTError::NotSupported));
+        let id = format!("symbol={}", market.id);
+        let interval = format!("interval={}", interval);
+        let start = format!("startTime={}", since * 1000);
+        let limit = format!("limit={}", limit);
+        Box::from(get_api!(self.exchange, "public", "klines", id.as_str(), interval.as_str(), start.as_str(), limit.as_str())
+            .and_then(move |json| Self::parse_ohlcv(&json)))
+    }
+
+    fn fetch_ticker(&self, symbol: &str) -> FetchTickerResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let id = format!("symbol={}", market.id);
+        Box::from(get_api!(self.exchange, "public", "ticker/24hr", id.as_str())
+            .and_then(move |json| Ok(Self::parse_ticker(&json, market.symbol))))
+    }
+
+    fn fetch_tickers(&self, symbols: Option<&[&str]>) -> FetchTickersResult {
+        let symbols: Option<Vec<String>> = symbols.map(|symbols| symbols.iter().map(|symbol| String::from(*symbol)).collect());
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api_with_cost("public", ApiMethod::Get, "ticker/24hr", &[], NO_SYMBOL_COST)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut tickers = HashMap::<String, Ticker>::new();
+                for ticker in as_array!(json, "tickers")? {
+                    let id = as_str!(ticker["symbol"], "ticker->symbol")?;
+                    if let Some(market) = markets.values().find(|market| market.id == id) {
+                        if symbols.as_ref().map_or(true, |symbols| symbols.contains(&market.symbol)) {
+                            tickers.insert(market.symbol.clone(), Self::parse_ticker(ticker, market.symbol.clone()));
+                        }
+                    }
+                }
+                Ok(tickers)
+            }))
+    }
+
+    ///
+    /// Valid limits are 5, 10, 20, 50, 100, 500 and 1000 (default 100)
+    ///
+    fn fetch_order_book(&self, symbol: &str, limit: Option<i64>) -> FetchOrderBookResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("symbol={}", market.id)];
+        if let Some(limit) = limit {
+            params.push(format!("limit={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api_with_cost("public", ApiMethod::Get, "depth", &params, Self::depth_cost(limit))
+            .and_then(move |json| {
+                let mut orderbook = parse_order_book(&json, None, "bids", "asks", &0, &1);
+                orderbook.nonce = json["lastUpdateId"].as_i64();
+                Ok(orderbook)
+            }))
+    }
+
+    ///
+    /// Aggregated trades, aggTrades only accept a one hour window after since
+    ///
+    fn fetch_trades(&self, symbol: &str, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("symbol={}", market.id)];
+        if let Some(since) = since {
+            params.push(format!("startTime={}", since));
+            params.push(format!("endTime={}", since + 3600000));
+        }
+        if let Some(limit) = limit {
+            params.push(format!("limit={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "aggTrades", &params)
+            .and_then(move |json| {
+                let mut trades = Vec::new();
+                for trade in as_array!(json, "trades")? {
+                    trades.push(Self::parse_trade(trade, market.symbol.clone(), &currencies)?);
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(filter_by_symbol_since_limit(trades, None, since, limit))
+            }))
+    }
+
+    ///
+    /// Binance require the symbol
+    ///
+    fn fetch_my_trades(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let symbol = try_future_box!(symbol.ok_or(CCXTError::ArgumentsRequired));
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("symbol={}", market.id)];
+        if let Some(since) = since {
+            params.push(format!("startTime={}", since));
+        }
+        if let Some(limit) = limit {
+            params.push(format!("limit={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Get, "myTrades", &params)
+            .and_then(move |json| {
+                let mut trades = Vec::new();
+                for trade in as_array!(json, "mytrades")? {
+                    trades.push(Self::parse_trade(trade, market.symbol.clone(), &currencies)?);
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(filter_by_symbol_since_limit(trades, None, since, limit))
+            }))
+    }
+
+    fn fetch_currencies(&self) -> FetchCurrenciesResult {
+        Box::new(ok(self.exchange.currencies_from_markets()))
+    }
+
+    fn fetch_balance(&self) -> FetchBalanceResult {
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(get_api!(self.exchange, "private", "account")
+            .and_then(move |json| {
+                let mut balance = Balance::default();
+                for account in as_array!(json["balances"], "balance->balances")? {
+                    let code = common_currency_code(&currencies, as_str!(account["asset"], "balance->asset")?);
+                    balance.currencies.insert(code, CurrencyBalance::new(safe_f64!(account["free"]), safe_f64!(account["locked"]), None));
+                }
+                balance.info = Some(json);
+                Ok(balance)
+            }))
+    }
+
+    ///
+    /// Limit orders are good till canceled (see the defaultTimeInForce option)
+    /// Stop orders are not supported as they need a stop price
+    ///
+    fn create_order(&self, symbol: &str, order_type: OrderType, side: OrderSide, amount: f64, price: Option<f64>) -> OrderResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let order_type = match order_type {
+            OrderType::Market => String::from("MARKET"),
+            OrderType::Limit => String::from("LIMIT"),
+            OrderType::Stop | OrderType::StopLimit => return Box::new(err(CCXTError::InvalidOrder.into())),
+            OrderType::Other(order_type) => order_type.to_uppercase(),
+        };
+        let side = match side {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        };
+        let mut params = vec![
+            format!("symbol={}", market.id),
+            format!("side={}", side),
+            format!("type={}", order_type),
+            format!("quantity={}", truncate_to_precision(amount, market.precision.0 as usize)),
+            String::from("newOrderRespType=RESULT"),
+        ];
+        if order_type == "LIMIT" || order_type == "LIMIT_MAKER" {
+            let price = try_future_box!(price.ok_or(CCXTError::InvalidOrder));
+            params.push(format!("price={}", Self::price_to_precision(&market, price)));
+        }
+        if order_type == "LIMIT" {
+            let time_in_force = self.exchange.option("defaultTimeInForce").as_str().unwrap_or("GTC");
+            params.push(format!("timeInForce={}", time_in_force));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "order", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_order(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    ///
+    /// Binance require the symbol
+    ///
+    fn cancel_order(&self, id: &str, symbol: Option<&str>) -> OrderResult {
+        let symbol = try_future_box!(symbol.ok_or(CCXTError::ArgumentsRequired));
+        let market = try_future_box!(self.exchange.market(symbol));
+        let symbol = format!("symbol={}", market.id);
+        let id = format!("orderId={}", id);
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Delete, "order", &[symbol.as_str(), id.as_str()])
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_order(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    ///
+    /// Binance require the symbol
+    ///
+    fn fetch_order(&self, id: &str, symbol: Option<&str>) -> OrderResult {
+        let symbol = try_future_box!(symbol.ok_or(CCXTError::ArgumentsRequired));
+        let market = try_future_box!(self.exchange.market(symbol));
+        let symbol = format!("symbol={}", market.id);
+        let id = format!("orderId={}", id);
+        let lock = self.exchange.market.clone();
+        Box::from(get_api!(self.exchange, "private", "order", symbol.as_str(), id.as_str())
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_order(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    ///
+    /// Binance require the symbol
+    ///
+    fn fetch_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        let symbol = try_future_box!(symbol.ok_or(CCXTError::ArgumentsRequired));
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("symbol={}", market.id)];
+        if let Some(since) = since {
+            params.push(format!("startTime={}", since));
+        }
+        if let Some(limit) = limit {
+            params.push(format!("limit={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Get, "allOrders", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_orders(json, markets.as_ref().ok_or(CCXTError::ExchangeError)?, None, since, limit)
+            }))
+    }
+
+    ///
+    /// Without symbol the request weight is 40, avoid calling it often
+    ///
+    fn fetch_open_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        let mut params = Vec::new();
+        if let Some(symbol) = symbol {
+            params.push(format!("symbol={}", try_future_box!(self.exchange.market(symbol)).id));
+        }
+        let cost = if params.is_empty() { NO_SYMBOL_COST } else { 1.0 };
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api_with_cost("private", ApiMethod::Get, "openOrders", &params, cost)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_orders(json, markets.as_ref().ok_or(CCXTError::ExchangeError)?, None, since, limit)
+            }))
+    }
+
+    fn fetch_markets(&mut self) -> LoadMarketResult {
+        let lock = self.exchange.market.clone();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(get_api!(self.exchange, "public", "exchangeInfo")
+            .and_then(move |re| {
+                let markets = Self::parse_markets(re, &currencies)?;
+                *lock.write().unwrap() = Some(markets);
+                Ok(lock)
+            }))
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use tokio::runtime::current_thread::block_on_all;
+    use super::Binance;
+    use futures::Future;
+    use crate::prelude::*;
+    use crate::base::exchange::{ExchangeTrait, Request, RequestMethod, RequestBody};
+    #[test]
+    #[ignore]
+    fn test_plateform() {
+        let ohlcv = block_on_all(Binance::new().and_then(|exchange| {
+            exchange.fetch_ohlcv("BTC/USDT", CandleTime::_1H, 1540000000, 100)
+        })).unwrap();
+        assert!(!ohlcv.is_empty());
+        assert!(ohlcv.iter().all(|candle| candle.timestamp >= 1540000000 && candle.lowest <= candle.highest));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ticker() {
+        let ticker = block_on_all(Binance::new().and_then(|exchange| exchange.fetch_ticker("BTC/USDT"))).unwrap();
+        assert_eq!(ticker.symbol, "BTC/USDT");
+        assert!(ticker.bid.unwrap() <= ticker.ask.unwrap());
+        assert!(ticker.last.is_some());
+    }
+
+    #[test]
+    fn test_sign_request() {
+        //Example of the signed endpoint documentation
+        let params = vec!["symbol=LTCBTC", "side=BUY", "type=LIMIT", "timeInForce=GTC", "quantity=1", "price=0.1"];
+        let params = params.into_iter().map(String::from).collect();
+        let mut request = Request::new("https://api.binance.com/api/v3/order".parse().unwrap(), RequestMethod::Post(Vec::new(), RequestBody::UrlEncoded(params)));
+        Binance::sign_request(&mut request, "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A",
+            "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j", 1499827319559, 5000);
+        assert_eq!(request.encoded_params(), "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000\
+            &timestamp=1499827319559&signature=c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71");
+        assert!(request.headers.contains(&(String::from("X-MBX-APIKEY"), String::from("vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A"))));
+    }
+
+    #[test]
+    fn test_parse_markets() {
+        let json: serde_json::Value = serde_json::from_str(r#"{"symbols": [
+            {"symbol": "ETHBTC", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "BTC", "baseAssetPrecision": 8, "quotePrecision": 8, "filters": [
+                {"filterType": "PRICE_FILTER", "minPrice": "0.00000100", "maxPrice": "100000.00000000", "tickSize": "0.00000100"},
+                {"filterType": "LOT_SIZE", "minQty": "0.00100000", "maxQty": "100000.00000000", "stepSize": "0.00100000"},
+                {"filterType": "MIN_NOTIONAL", "minNotional": "0.00100000"}
+            ]},
+            {"symbol": "BCCUSDT", "status": "BREAK", "baseAsset": "BCC", "quoteAsset": "USDT", "filters": []},
+            {"symbol": "123456", "status": "TRADING", "baseAsset": "123", "quoteAsset": "456", "filters": []}
+        ]}"#).unwrap();
+        let mut currencies = std::collections::HashMap::new();
+        currencies.insert(String::from("BCC"), String::from("BCH"));
+        let markets = Binance::parse_markets(json, &currencies).unwrap();
+        assert_eq!(markets.len(), 2);
+        let eth = &markets["ETH/BTC"];
+        assert!(eth.active);
+        assert_eq!(eth.precision, (3.0, 6.0));
+        assert_eq!(eth.limits.amount, (0.001, 100000.0));
+        assert_eq!(eth.limits.price, (0.000001, 100000.0));
+        assert_eq!(eth.limits.cost, (0.001, 0.0));
+        assert!(!markets["BCH/USDT"].active);
+    }
+
+    #[test]
+    fn test_parse_ohlcv() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            [1499040000000, "0.01634790", "0.80000000", "0.01575800", "0.01577100", "148976.11427815", 1499644799999, "2434.19055334", 308, "1756.87402397", "28.46694368", "0"]
+        ]"#).unwrap();
+        let ohlcv = Binance::parse_ohlcv(&json).unwrap();
+        assert_eq!(ohlcv.len(), 1);
+        assert_eq!(ohlcv[0].timestamp, 1499040000);
+        assert_eq!((ohlcv[0].open, ohlcv[0].highest, ohlcv[0].lowest, ohlcv[0].losing), (0.0163479, 0.8, 0.015758, 0.015771));
+        assert_eq!(ohlcv[0].volume, 148976.11427815);
+    }
+
+    #[test]
+    fn test_parse_ticker() {
+        let ticker: serde_json::Value = serde_json::from_str(r#"{
+            "symbol": "BTCUSDT", "priceChange": "-94.99999800", "priceChangePercent": "-1.464",
+            "weightedAvgPrice": "6451.23", "prevClosePrice": "6491.00", "lastPrice": "6396.00", "lastQty": "0.2",
+            "bidPrice": "6395.50", "bidQty": "1.5", "askPrice": "6396.50", "askQty": "0.8", "openPrice": "6490.99",
+            "highPrice": "6520.00", "lowPrice": "6380.00", "volume": "18934.41", "quoteVolume": "122150326.54",
+            "openTime": 1540000000000, "closeTime": 1540086400000, "count": 76
+        }"#).unwrap();
+        let ticker = Binance::parse_ticker(&ticker, String::from("BTC/USDT"));
+        assert_eq!(ticker.symbol, "BTC/USDT");
+        assert_eq!(ticker.timestamp, 1540086400000);
+        assert_eq!((ticker.bid, ticker.bid_volume), (Some(6395.5), Some(1.5)));
+        assert_eq!((ticker.ask, ticker.ask_volume), (Some(6396.5), Some(0.8)));
+        assert_eq!((ticker.high, ticker.low), (Some(6520.0), Some(6380.0)));
+        assert_eq!((ticker.open, ticker.last, ticker.close), (Some(6490.99), Some(6396.0), Some(6396.0)));
+        assert_eq!(ticker.percentage, Some(-1.464));
+        assert_eq!((ticker.base_volume, ticker.quote_volume), (Some(18934.41), Some(122150326.54)));
+    }
+
+    #[test]
+    fn test_depth_cost() {
+        assert_eq!(Binance::depth_cost(None), 1.0);
+        assert_eq!(Binance::depth_cost(Some(500)), 5.0);
+        assert_eq!(Binance::depth_cost(Some(1000)), 10.0);
+        assert_eq!(Binance::depth_cost(Some(5000)), 50.0);
+    }
+}
@@ -1,13 +1,14 @@
-use std::sync::{Once, Arc, RwLock};
+use std::sync::Once;
 use super::prelude::*;
+use super::base::crypto::{hmac_sha384_hex, base64_encode};
 use futures::Future;
 use futures::future::{ok, err};
 use serde_json::Value;
-use hyper::rt;
 
 static INIT: Once = Once::new();
 static mut BITFINEX_EXCHANGE: Option<Exchange<HttpConnector>> = None;
 
+#[derive(Clone)]
 pub struct Bitfinex {
     exchange: Exchange<HttpConnector>,
 }
@@ -21,16 +22,126 @@ impl Bitfinex {
                 {
                     "id": "bitfinex",
                     "name": "Bitfinex",
+                    "rateLimit": 1500,
+                    "options": {
+                        "currencyNames": {
+                            "AGI": "agi",
+                            "AID": "aid",
+                            "AIO": "aio",
+                            "ANT": "ant",
+                            "AVT": "aventus",
+                            "BAT": "bat",
+                            "BCH": "bcash",
+                            "BCI": "bci",
+                            "BFT": "bft",
+                            "BTC": "bitcoin",
+                            "BTG": "bgold",
+                            "CFI": "cfi",
+                            "DAI": "dai",
+                            "DADI": "dad",
+                            "DASH": "dash",
+                            "DATA": "datacoin",
+                            "DTH": "dth",
+                            "EDO": "eidoo",
+                            "ELF": "elf",
+                            "EOS": "eos",
+                            "ETC": "ethereumc",
+                            "ETH": "ethereum",
+                            "ETP": "metaverse",
+                            "FUN": "fun",
+                            "GNT": "golem",
+                            "IOST": "ios",
+                            "IOTA": "iota",
+                            "LRC": "lrc",
+                            "LTC": "litecoin",
+                            "LYM": "lym",
+                            "MANA": "mna",
+                            "MIT": "mit",
+                            "MKR": "mkr",
+                            "MTN": "mtn",
+                            "NEO": "neo",
+                            "ODE": "ode",
+                            "OMG": "omisego",
+                            "OMNI": "mastercoin",
+                            "QASH": "qash",
+                            "QTUM": "qtum",
+                            "RCN": "rcn",
+                            "RDN": "rdn",
+                            "REP": "rep",
+                            "REQ": "req",
+                            "RLC": "rlc",
+                            "SAN": "santiment",
+                            "SNGLS": "sng",
+                            "SNT": "status",
+                            "SPANK": "spk",
+                            "STORJ": "stj",
+                            "TNB": "tnb",
+                            "TRX": "trx",
+                            "USD": "wire",
+                            "UTK": "utk",
+                            "USDT": "tetheruso",
+                            "VEE": "vee",
+                            "WAX": "wax",
+                            "XLM": "xlm",
+                            "XMR": "monero",
+                            "XRP": "ripple",
+                            "XVG": "xvg",
+                            "YOYOW": "yoyow",
+                            "ZEC": "zcash",
+                            "ZRX": "zrx"
+                        }
+                    },
                     "api-urls": {
+                        "v2": "https://api.bitfinex.com/v2",
                         "public": "https://api.bitfinex.com/v1",
                         "private": "https://api.bitfinex.com/v1"
                     },
                     "api": {
-                        'v2': {
-                            'get': [
-                                'candles/trade:{timeframe}:{symbol}/{section}',
-                                'candles/trade:{timeframe}:{symbol}/last',
-                                'candles/trade:{timeframe}:{symbol}/hist'
+                        "v2": {
+                            "get": [
+                                "candles/trade:{timeframe}:{symbol}/{section}",
+                                "candles/trade:{timeframe}:{symbol}/last",
+                                "candles/trade:{timeframe}:{symbol}/hist"
+                            ]
+                        },
+                        "private": {
+                            "post": [
+                                "account_fees",
+                                "account_infos",
+                                "balances",
+                                "basket_manage",
+                                "credits",
+                                "deposit/new",
+                                "funding/close",
+                                "history",
+                                "history/movements",
+                                "key_info",
+                                "margin_infos",
+                                "mytrades",
+                                "mytrades_funding",
+                                "offer/cancel",
+                                "offer/new",
+                                "offer/status",
+                                "offers",
+                                "offers/hist",
+                                "order/cancel",
+                                "order/cancel/all",
+                                "order/cancel/multi",
+                                "order/cancel/replace",
+                                "order/new",
+                                "order/new/multi",
+                                "order/status",
+                                "orders",
+                                "orders/hist",
+                                "position/claim",
+                                "position/close",
+                                "positions",
+                                "summary",
+                                "taken_funds",
+                                "total_taken_funds",
+                                "transfer",
+                                "unused_taken_funds",
+                                "withdraw"
                             ]
                         },
                         "public": {
@@ -48,6 +159,29 @@ impl Bitfinex {
                             ]
                         }
                     },
+                    "exceptions": {
+                        "exact": {
+                            "temporarily_unavailable": "ExchangeNotAvailable",
+                            "Order could not be cancelled.": "OrderNotFound",
+                            "No such order found.": "OrderNotFound",
+                            "Order price must be positive.": "InvalidOrder",
+                            "Could not find a key matching the given X-BFX-APIKEY.": "AuthenticationError",
+                            "This API key does not have permission for this action": "AuthenticationError",
+                            "Key price should be a decimal number, e.g. \"123.456\"": "InvalidOrder",
+                            "Key amount should be a decimal number, e.g. \"123.456\"": "InvalidOrder",
+                            "ERR_RATE_LIMIT": "DDoSProtection",
+                            "Ratelimit": "DDoSProtection",
+                            "Nonce is too small.": "InvalidNonce",
+                            "No summary found.": "ExchangeError",
+                            "Cannot evaluate your available balance, please try again": "ExchangeNotAvailable"
+                        },
+                        "broad": {
+                            "Invalid order: not enough exchange balance for ": "InsufficientFunds",
+                            "Invalid order: minimum size for ": "InvalidOrder",
+                            "Invalid order": "InvalidOrder",
+                            "The available balance is only": "InsufficientFunds"
+                        }
+                    },
                     "commonCurrencies": {
                         "ABS": "ABYSS",
                         "AIO": "AION",
@@ -84,10 +218,254 @@ impl Bitfinex {
         let connector = HttpConnector::new();
         let mut exchange = unsafe {BITFINEX_EXCHANGE.as_ref().unwrap().clone()};
         exchange.set_connector(Box::new(connector));
+        exchange.set_sign_hook(Self::sign);
         let mut exchange = Bitfinex { exchange };
         Box::from(exchange.fetch_markets().and_then(|_| ok(exchange)))
     }
 
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.exchange.set_credentials(credentials);
+    }
+
+    ///
+    /// Balance of one of the account wallets: "exchange", "margin" or "funding"
+    /// 
+    pub fn fetch_wallet_balance(&self, wallet: &str) -> FetchBalanceResult {
+        let wallet = String::from(wallet);
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "balances", &[])
+            .and_then(move |json| Self::parse_balance(json, &wallet, &currencies)))
+    }
+
+    fn parse_balance(json: Value, wallet: &str, currencies: &HashMap<String, String>) -> Result<Balance, Error> {
+        let mut balance = Balance::default();
+        for account in as_array!(json, "balance")? {
+            if account["type"].as_str() != Some(wallet) {
+                continue;
+            }
+            let currency = as_str!(account["currency"], "balance->currency")?.to_uppercase();
+            let code = common_currency_code(currencies, &currency);
+            balance.currencies.insert(code, CurrencyBalance::new(safe_f64!(account["available"]), None, safe_f64!(account["amount"])));
+        }
+        balance.info = Some(json);
+        Ok(balance)
+    }
+
+    ///
+    /// v1 private requests send their parametters, the request path and a nonce as a base64 json payload
+    /// signed with HMAC-SHA384(secret, payload), see https://docs.bitfinex.com/v1/docs/rest-auth
+    /// 
+    fn sign(exchange: &Exchange<HttpConnector>, api: &str, request: &mut Request) -> Result<(), Error> {
+        if api != "private" {
+            return Ok(());
+        }
+        exchange.check_required_credentials()?;
+        let credentials = exchange.credentials();
+        let api_key = credentials.api_key.clone().unwrap_or_default();
+        let secret = credentials.secret.clone().unwrap_or_default();
+        let path = String::from(request.path.path());
+        let mut payload = match request.body() {
+            RequestBody::UrlEncoded(params) => RequestBody::json_from_params(params),
+            RequestBody::Json(json) => RequestBody::Json(json.clone()),
+            RequestBody::Empty => RequestBody::json_from_params(&[]),
+        };
+        if let RequestBody::Json(Value::Object(object)) = &mut payload {
+            object.insert(String::from("request"), Value::String(path));
+            //Bitfinex reject a nonce that is not greater than the previous one
+            object.insert(String::from("nonce"), Value::String(exchange.increasing_nonce().to_string()));
+        }
+        let encoded = base64_encode(payload.encode().as_bytes());
+        let signature = hmac_sha384_hex(secret.as_bytes(), encoded.as_bytes());
+        request.method = RequestMethod::Post(Vec::new(), payload);
+        request.add_header("X-BFX-APIKEY", api_key.as_str());
+        request.add_header("X-BFX-PAYLOAD", encoded.as_str());
+        request.add_header("X-BFX-SIGNATURE", signature.as_str());
+        Ok(())
+    }
+
+    fn time_frame(time: CandleTime) -> Option<&'static str> {
+        match time {
+            CandleTime::_1M => Some("1m"),
+            CandleTime::_5M => Some("5m"),
+            CandleTime::_15M => Some("15m"),
+            CandleTime::_30M => Some("30m"),
+            CandleTime::_1H => Some("1h"),
+            CandleTime::_3H => Some("3h"),
+            CandleTime::_6H => Some("6h"),
+            CandleTime::_12H => Some("12h"),
+            CandleTime::_1D => Some("1D"),
+            CandleTime::_1W => Some("7D"),
+            CandleTime::_1MONTH => Some("1M"),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Deposit and withdrawal methods are named after the currency (ex: BTC -> bitcoin)
+    /// 
+    fn currency_name(&self, code: &str) -> Result<String, Error> {
+        let name = self.exchange.option("currencyNames")[code].as_str().ok_or(CCXTError::NotSupported)?;
+        Ok(String::from(name))
+    }
+
+    ///
+    /// deposit/new, renew=1 generate a new address
+    /// Currencies with a shared address (ex: XRP) return it in address_pool and the tag in address
+    /// 
+    fn deposit_address(&self, code: &str, renew: bool) -> DepositAddressResult {
+        let name = try_future_box!(self.currency_name(code));
+        let method = format!("method={}", name);
+        let renew = format!("renew={}", if renew { 1 } else { 0 });
+        let code = String::from(code);
+        Box::from(post_api!(self.exchange, "private", "deposit/new", method.as_str(), "wallet_name=exchange", renew.as_str())
+            .and_then(move |json| {
+                let (address, tag) = match json.get("address_pool") {
+                    Some(pool) => (parse_deposit_address(pool)?, safe_string!(json["address"])),
+                    None => (parse_deposit_address(&json["address"])?, None),
+                };
+                Ok(DepositAddress {
+                    currency: code,
+                    address,
+                    tag,
+                    info: Some(json),
+                })
+            }))
+    }
+
+    ///
+    /// Parse symbols_details, price_precision is a number of significant digits
+    /// 
+    fn parse_markets(re: Value, currencies: &HashMap<String, String>) -> Result<HashMap<String, Market>, Error> {
+        let mut markets = HashMap::<String, Market>::new();
+        for market in as_array!(re, "markets")?.into_iter() {
+            let pair = as_str!(market["pair"], "market->pair")?;
+            let id = String::from(pair).to_uppercase();
+            //Pairs are 3+3 letters (btcusd) or separated by a colon for longer currency ids (dusk:usd)
+            let (base_id, quote_id) = match id.find(':') {
+                Some(index) => (String::from(&id[..index]), String::from(&id[index + 1..])),
+                None if id.len() == 6 && id.is_ascii() => (String::from(&id[0..3]), String::from(&id[3..6])),
+                None => continue,
+            };
+            let base = common_currency_code(currencies, &base_id);
+            let quote = common_currency_code(currencies, &quote_id);
+            let symbol = format!("{}/{}", base, quote);
+            let price_precision = as_i64!(market["price_precision"], "market->precision")?;
+            //Order sizes are strings
+            let limits_amount = (safe_f64!(market["minimum_order_size"]).unwrap_or(0.0), safe_f64!(market["maximum_order_size"]).unwrap_or(0.0));
+            let limits_price = (10f64.powi(-price_precision as i32), 10f64.powi(price_precision as i32));
+            let limits_cost = (limits_amount.0 * limits_price.0, 0.0);
+            markets.insert(symbol.clone(), Market {
+                id,
+                symbol,
+                base,
+                quote,
+                base_id,
+                quote_id,
+                active: true,
+                precision: (price_precision as f64, price_precision as f64),
+                limits: MarketLimits::new(limits_amount, limits_price, limits_cost),
+                market_type: MarketType::Spot,
+                linear: None,
+                settle: None,
+                contract_size: None,
+                expiry: None,
+                underlying_index: None,
+                info: None,
+            });
+        }
+        Ok(markets)
+    }
+
+    ///
+    /// Parse a movement of history/movements, timestamps are in seconds and fees are negative
+    /// 
+    fn parse_transaction(transaction: &Value, code: &str) -> Result<Transaction, Error> {
+        let transaction_type = match as_str!(transaction["type"], "movement->type")? {
+            "DEPOSIT" => TransactionType::Deposit,
+            "WITHDRAWAL" => TransactionType::Withdrawal,
+            _ => return Err(CCXTError::BadResponse.into()),
+        };
+        let status = match transaction["status"].as_str() {
+            Some("COMPLETED") => TransactionStatus::Ok,
+            Some("CANCELED") => TransactionStatus::Canceled,
+            //Happen on double spend attempts
+            Some("ZEROCONFIRMED") => TransactionStatus::Failed,
+            _ => TransactionStatus::Pending,
+        };
+        Ok(Transaction {
+            id: safe_string!(transaction["id"]).ok_or(CCXTError::BadResponse)?,
+            txid: safe_string!(transaction["txid"]),
+            timestamp: safe_f64!(transaction["timestamp_created"]).map(|timestamp| (timestamp * 1000.0) as i64),
+            updated: safe_f64!(transaction["timestamp"]).map(|timestamp| (timestamp * 1000.0) as i64),
+            address: safe_string!(transaction["address"]),
+            tag: None,
+            transaction_type,
+            amount: safe_f64!(transaction["amount"]).ok_or(CCXTError::BadResponse)?,
+            currency: String::from(code),
+            status,
+            fee: safe_f64!(transaction["fee"]).map(|cost| Fee {
+                cost: cost.abs(),
+                currency: Some(String::from(code)),
+                rate: None,
+            }),
+            info: Some(transaction.clone()),
+        })
+    }
+
+    ///
+    /// Public trades and mytrades share the same format, only the later have the fee fields
+    /// 
+    fn parse_trade(trade: &Value, symbol: String, currencies: &HashMap<String, String>) -> Result<Trade, Error> {
+        let timestamp = safe_f64!(trade["timestamp"]).ok_or(CCXTError::BadResponse)? as i64 * 1000;
+        let price = safe_f64!(trade["price"]).ok_or(CCXTError::BadResponse)?;
+        let amount = safe_f64!(trade["amount"]).ok_or(CCXTError::BadResponse)?;
+        //Fees are reported as negative amounts
+        let fee = safe_f64!(trade["fee_amount"]).map(|cost| Fee {
+            cost: -cost,
+            currency: trade["fee_currency"].as_str().map(|currency| common_currency_code(currencies, &currency.to_uppercase())),
+            rate: None,
+        });
+        Ok(Trade {
+            id: safe_string!(trade["tid"]),
+            timestamp,
+            symbol,
+            order: safe_string!(trade["order_id"]),
+            side: trade["type"].as_str().and_then(|side| side.parse().ok()),
+            taker_or_maker: None,
+            price,
+            amount,
+            cost: price * amount,
+            fee,
+            info: Some(trade.clone()),
+        })
+    }
+
+    fn parse_ticker(ticker: &Value, symbol: String) -> Ticker {
+        let timestamp = safe_f64!(ticker["timestamp"]).map(|timestamp| (timestamp * 1000.0) as i64).unwrap_or_else(milliseconds);
+        let last = safe_f64!(ticker["last_price"]);
+        Ticker {
+            symbol,
+            timestamp,
+            high: safe_f64!(ticker["high"]),
+            low: safe_f64!(ticker["low"]),
+            bid: safe_f64!(ticker["bid"]),
+            bid_volume: None,
+            ask: safe_f64!(ticker["ask"]),
+            ask_volume: None,
+            vwap: None,
+            open: None,
+            close: last,
+            last,
+            previous_close: None,
+            change: None,
+            percentage: None,
+            average: safe_f64!(ticker["mid"]),
+            base_volume: safe_f64!(ticker["volume"]),
+            quote_volume: None,
+            info: Some(ticker.clone()),
+        }
+    }
+
 }
 
 // #macro_rules! value_unpack_or {
@@ -99,48 +477,192 @@ impl Bitfinex {
 use std::collections::HashMap;
 impl ExchangeTrait for Bitfinex {
 
+    ///
+    /// v2 candles, since is in seconds like the returned timestamps
+    /// Candles are arrays of [timestamp (ms), open, close, high, low, volume]
+    /// 
     fn fetch_ohlcv(&self, symbol: &str, timeframe: CandleTime, since: i64, limit: i64) -> FetchOhlcvResult {
-        let market = self.exchange.get_market_by_symbol(symbol).unwrap();//TODO not unwrap
-        let limit = format!("limit={}", limit);
-        let since = format!("since={}", since);
+        let market = try_future_box!(self.exchange.market(symbol));
+        let timeframe = try_future_box!(Self::time_frame(timeframe).ok_or(CCXTError::NotSupported));
         let v2id = format!("t{}", market.id);
-        Box::from(self.exchange.call_api("public", ApiMethod::Get, "candles/trade:timeframe:symbol/section", &[limit.as_str(), since.as_str()]).and_then(move |json| {
-            let ohlcv = Vec::<Ohlcv>::new();
-            
-            ok(ohlcv)
-        }))
+        let limit = format!("limit={}", limit);
+        let start = format!("start={}", since * 1000);
+        Box::from(get_api!(self.exchange, "v2", "candles/trade:timeframe:symbol/section", timeframe, v2id.as_str(), "hist", limit.as_str(), start.as_str(), "sort=1")
+            .and_then(move |json| {
+                let mut ohlcv = Vec::<Ohlcv>::new();
+                for candle in as_array!(json, "ohlcv")? {
+                    ohlcv.push(Ohlcv {
+                        timestamp: as_i64!(candle[0], "ohlcv->timestamp")? / 1000,
+                        open: as_f64!(candle[1], "ohlcv->open")?,
+                        losing: as_f64!(candle[2], "ohlcv->close")?,
+                        highest: as_f64!(candle[3], "ohlcv->high")?,
+                        lowest: as_f64!(candle[4], "ohlcv->low")?,
+                        volume: as_f64!(candle[5], "ohlcv->volume")?,
+                    });
+                }
+                Ok(ohlcv)
+            }))
     }
 
-    fn fetch_markets(&mut self) -> LoadMarketResult {
-        fn parse_markets(re: Value) -> Result<HashMap<String, Market>, Error> {
-            let mut markets = HashMap::<String, Market>::new();
-            for market in as_array!(re, "markets")?.into_iter() {
-                let pair = as_str!(market["pair"], "market->pair")?;
-                let id = String::from(pair).to_uppercase();
-                let base_id = String::from(&pair[0..3]);
-                let quote_id = String::from(&pair[3..6]);
-                let symbol = format!("{}/{}", base_id, quote_id);
-                let price_precision = as_i64!(market["price_precision"], "market->precision")?;
-                let limits_amount = (as_i64_or!(market["minimum_order_size"], 0) as f64, as_i64_or!(market["maximum_order_size"], 0) as f64);
-                let limits_price = ((-price_precision).pow(10) as f64, price_precision.pow(10) as f64);
-                let limits_cost = (limits_amount.0 * limits_price.0, 0.0);
-                markets.insert(symbol.clone(), Market {
-                    id,
-                    symbol,
-                    base_id,
-                    quote_id,
-                    active: true,
-                    precision: (price_precision as f64, price_precision as f64),
-                    limits: MarketLimits::new(limits_amount, limits_price, limits_cost),
-                    info: None,
-                });
-            }
-            Ok(markets)
+    fn fetch_ticker(&self, symbol: &str) -> FetchTickerResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "pubticker/symbol", &[market.id.as_str()])
+            .and_then(move |json| Ok(Self::parse_ticker(&json, market.symbol))))
+    }
+
+    fn fetch_tickers(&self, symbols: Option<&[&str]>) -> FetchTickersResult {
+        let symbols: Option<Vec<String>> = symbols.map(|symbols| symbols.iter().map(|symbol| String::from(*symbol)).collect());
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "tickers", &[])
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut tickers = HashMap::<String, Ticker>::new();
+                for ticker in as_array!(json, "tickers")? {
+                    let id = as_str!(ticker["pair"], "ticker->pair")?.to_uppercase();
+                    if let Some(market) = markets.values().find(|market| market.id == id) {
+                        if symbols.as_ref().map_or(true, |symbols| symbols.contains(&market.symbol)) {
+                            tickers.insert(market.symbol.clone(), Self::parse_ticker(ticker, market.symbol.clone()));
+                        }
+                    }
+                }
+                Ok(tickers)
+            }))
+    }
+
+    fn fetch_order_book(&self, symbol: &str, limit: Option<i64>) -> FetchOrderBookResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let limit_bids = format!("limit_bids={}", limit.unwrap_or(50));
+        let limit_asks = format!("limit_asks={}", limit.unwrap_or(50));
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "book/symbol", &[market.id.as_str(), limit_bids.as_str(), limit_asks.as_str()])
+            .and_then(move |json| Ok(parse_order_book(&json, None, "bids", "asks", "price", "amount"))))
+    }
+
+    fn fetch_trades(&self, symbol: &str, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![market.id.clone(), format!("limit_trades={}", limit.unwrap_or(50))];
+        if let Some(since) = since {
+            params.push(format!("timestamp={}", since / 1000));
         }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "trades/symbol", &params)
+            .and_then(move |json| {
+                let mut trades = Vec::new();
+                for trade in as_array!(json, "trades")? {
+                    trades.push(Self::parse_trade(trade, market.symbol.clone(), &currencies)?);
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(trades)
+            }))
+    }
+
+    ///
+    /// Bitfinex require the symbol, since is sent in seconds
+    /// 
+    fn fetch_my_trades(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let symbol = try_future_box!(symbol.ok_or(CCXTError::ArgumentsRequired));
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("symbol={}", market.id)];
+        if let Some(since) = since {
+            params.push(format!("timestamp={}", since / 1000));
+        }
+        if let Some(limit) = limit {
+            params.push(format!("limit_trades={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "mytrades", &params)
+            .and_then(move |json| {
+                let mut trades = Vec::new();
+                for trade in as_array!(json, "mytrades")? {
+                    trades.push(Self::parse_trade(trade, market.symbol.clone(), &currencies)?);
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(filter_by_symbol_since_limit(trades, None, since, limit))
+            }))
+    }
+
+    fn fetch_deposit_address(&self, code: &str) -> DepositAddressResult {
+        self.deposit_address(code, false)
+    }
+
+    fn create_deposit_address(&self, code: &str) -> DepositAddressResult {
+        self.deposit_address(code, true)
+    }
+
+    ///
+    /// Withdraw from the exchange wallet, a withdrawal_id of 0 mean that the request was rejected
+    /// 
+    fn withdraw(&self, code: &str, amount: f64, address: &str, tag: Option<&str>) -> WithdrawResult {
+        try_future_box!(check_address(address));
+        let name = try_future_box!(self.currency_name(code));
+        let mut params = vec![
+            format!("withdraw_type={}", name),
+            String::from("walletselected=exchange"),
+            //Sent as json strings, not numbers
+            format!("amount=\"{}\"", amount),
+            format!("address={}", address),
+        ];
+        if let Some(tag) = tag {
+            params.push(format!("payment_id=\"{}\"", tag));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let exceptions = self.exchange.exceptions();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "withdraw", &params)
+            .and_then(move |json| {
+                let response = as_array!(json, "withdraw")?.first().ok_or(CCXTError::NullResponse)?;
+                let id = response["withdrawal_id"].as_i64().unwrap_or(0);
+                if id == 0 {
+                    let message = response["message"].as_str().unwrap_or("");
+                    return Err(exceptions.find_broadly_matched_key(message).unwrap_or(CCXTError::ExchangeError).into());
+                }
+                Ok(WithdrawResponse {
+                    id: id.to_string(),
+                    info: Some(response.clone()),
+                })
+            }))
+    }
+
+    ///
+    /// Bitfinex require the currency, sent as the Bitfinex id of the markets (ex: DASH -> DSH), since is sent in seconds
+    /// 
+    fn fetch_transactions(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTransactionsResult {
+        let code = String::from(try_future_box!(code.ok_or(CCXTError::ArgumentsRequired)));
+        let id = try_future_box!(self.exchange.currency_id(&code).ok_or(CCXTError::ExchangeError));
+        let mut params = vec![format!("currency={}", id)];
+        if let Some(since) = since {
+            params.push(format!("since={}", since / 1000));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "history/movements", &params)
+            .and_then(move |json| {
+                let mut transactions = Vec::new();
+                for transaction in as_array!(json, "movements")? {
+                    transactions.push(Self::parse_transaction(transaction, &code)?);
+                }
+                transactions.sort_by_key(|transaction| transaction.timestamp);
+                Ok(filter_by_currency_since_limit(transactions, None, since, limit))
+            }))
+    }
+
+    fn fetch_currencies(&self) -> FetchCurrenciesResult {
+        Box::new(ok(self.exchange.currencies_from_markets()))
+    }
+
+    ///
+    /// Balance of the exchange wallet, use fetch_wallet_balance for the margin and funding wallets
+    /// 
+    fn fetch_balance(&self) -> FetchBalanceResult {
+        self.fetch_wallet_balance("exchange")
+    }
+
+    fn fetch_markets(&mut self) -> LoadMarketResult {
         let lock = self.exchange.market.clone();
+        let currencies = self.exchange.common_currencies.clone();
         Box::from(self.exchange.call_api("public", ApiMethod::Get, "symbols_details", &[])
             .and_then(move |re| {
-                match parse_markets(re) {
+                match Self::parse_markets(re, &currencies) {
                     Ok(result) =>{ 
                         *lock.write().unwrap() = Some(result);
                         ok(lock)
@@ -169,10 +691,44 @@ mod tests {
         rt::run(future::lazy(move||{
             Bitfinex::new().and_then(|exchange| {
                 println!("New bitfined exhange\nMarket : {:?}", exchange.exchange.market);
-                exchange.fetch_ohlcv("omg/usd", CandleTime::_1M, 10, 100)
+                exchange.fetch_ohlcv("OMG/USDT", CandleTime::_1M, 10, 100)
             })
             .map(|_|{})
             .map_err(|_|{})
         }));
     }
+
+    #[test]
+    fn test_parse_markets() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"pair": "btcusd", "price_precision": 5, "minimum_order_size": "0.0006", "maximum_order_size": "2000.0"},
+            {"pair": "dusk:usd", "price_precision": 5, "minimum_order_size": "20.0", "maximum_order_size": "250000.0"},
+            {"pair": "testbtcf0", "price_precision": 5, "minimum_order_size": "1.0", "maximum_order_size": "10.0"}
+        ]"#).unwrap();
+        let mut currencies = std::collections::HashMap::new();
+        currencies.insert(String::from("DSH"), String::from("DASH"));
+        let markets = Bitfinex::parse_markets(json, &currencies).unwrap();
+        assert_eq!(markets.len(), 2);
+        let btc = &markets["BTC/USD"];
+        assert_eq!((btc.base_id.as_str(), btc.quote_id.as_str()), ("BTC", "USD"));
+        assert_eq!(btc.limits.amount, (0.0006, 2000.0));
+        assert_eq!(btc.limits.price, (0.00001, 100000.0));
+        assert_eq!(markets["DUSK/USD"].limits.amount, (20.0, 250000.0));
+    }
+
+    #[test]
+    fn test_parse_balance() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"type": "exchange", "currency": "btc", "amount": "0.5", "available": "0.2"},
+            {"type": "margin", "currency": "btc", "amount": "3.0", "available": "3.0"},
+            {"type": "exchange", "currency": "dsh", "amount": "10", "available": "10"}
+        ]"#).unwrap();
+        let mut currencies = std::collections::HashMap::new();
+        currencies.insert(String::from("DSH"), String::from("DASH"));
+        let balance = Bitfinex::parse_balance(json, "exchange", &currencies).unwrap();
+        let btc = balance.get("BTC").unwrap();
+        assert_eq!((btc.free, btc.total), (0.2, 0.5));
+        assert!((btc.used - 0.3).abs() < 1e-9);
+        assert_eq!(balance.get("DASH").unwrap().total, 10.0);
+    }
 }
\ No newline at end of file
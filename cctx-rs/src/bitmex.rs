@@ -1,9 +1,10 @@
 use std::sync::{Once};//Arc, RwLock};
 use super::prelude::*;
+use super::base::crypto::hmac_sha256_hex;
 use std::collections::HashMap;
 use chrono::naive::NaiveDateTime;
 use futures::Future;
-use futures::future::{ok, err};
+use futures::future::{ok, err, loop_fn, Loop};
 use serde_json::Value;
 //use hyper::rt;
 //use std::cmp::{max, min};
@@ -11,10 +12,62 @@ use serde_json::Value;
 static INIT: Once = Once::new();
 static mut BITMEX_EXCHANGE: Option<Exchange<HttpConnector>> = None;
 
+#[derive(Clone)]
 pub struct Bitmex {
     exchange: Exchange<HttpConnector>,
 }
 
+///
+/// Active liquidation order, remaining is the quantity not yet taken over by the liquidation engine
+/// 
+#[derive(Debug, Clone)]
+pub struct Liquidation {
+    pub order_id: String,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub price: Option<f64>,
+    pub remaining: f64,
+    pub info: Option<Value>,
+}
+
+///
+/// Balance of the insurance fund at a given time (BTC)
+/// 
+#[derive(Debug, Clone)]
+pub struct InsuranceFund {
+    pub currency: String,
+    pub timestamp: i64,
+    pub balance: f64,
+    pub info: Option<Value>,
+}
+
+///
+/// Settlement of a future or of a swap funding, settlement_type is the raw Bitmex type (ex: Settlement, Delisted)
+/// 
+#[derive(Debug, Clone)]
+pub struct Settlement {
+    pub symbol: String,
+    pub timestamp: i64,
+    pub settlement_type: String,
+    pub settled_price: Option<f64>,
+    pub info: Option<Value>,
+}
+
+///
+/// 24h statistics of a root symbol (ex: XBT for every XBT contract)
+/// turnover and open value are in currency (BTC for XBt)
+/// 
+#[derive(Debug, Clone)]
+pub struct ExchangeStats {
+    pub root_symbol: String,
+    pub currency: Option<String>,
+    pub volume: Option<f64>,
+    pub turnover: Option<f64>,
+    pub open_interest: Option<f64>,
+    pub open_value: Option<f64>,
+    pub info: Option<Value>,
+}
+
 impl Bitmex {
 
     pub fn new() -> CCXTFut<Self> {
@@ -24,6 +77,26 @@ impl Bitmex {
                 {
                     "id": "Bitmex",
                     "name": "Bitmex",
+                    "rateLimit": 2000,
+                    "has": {
+                        "fetchOHLCV": true,
+                        "editOrder": true,
+                        "fetchOrder": true,
+                        "fetchOrders": true,
+                        "fetchOpenOrders": true,
+                        "fetchClosedOrders": true,
+                        "fetchMyTrades": true,
+                        "fetchDepositAddress": true,
+                        "withdraw": true,
+                        "fetchTransactions": true,
+                        "fetchDeposits": true,
+                        "fetchWithdrawals": true,
+                        "fetchLedger": true,
+                        "fetchPositions": true,
+                        "setLeverage": true,
+                        "fetchFundingRate": true,
+                        "fetchFundingHistory": true
+                    },
                     "api-urls": {
                         "public": "https://www.bitmex.com/api/v1",
                         "private": "https://www.bitmex.com/api/v1"
@@ -55,6 +128,81 @@ impl Bitmex {
                                 "trade",
                                 "trade/bucketed"
                             ]
+                        },
+                        "private": {
+                            "get": [
+                                "apiKey",
+                                "chat",
+                                "chat/channels",
+                                "chat/connected",
+                                "execution",
+                                "execution/tradeHistory",
+                                "notification",
+                                "order",
+                                "position",
+                                "user",
+                                "user/affiliateStatus",
+                                "user/checkReferralCode",
+                                "user/commission",
+                                "user/depositAddress",
+                                "user/margin",
+                                "user/minWithdrawalFee",
+                                "user/wallet",
+                                "user/walletHistory",
+                                "user/walletSummary"
+                            ],
+                            "post": [
+                                "apiKey",
+                                "apiKey/disable",
+                                "apiKey/enable",
+                                "chat",
+                                "order",
+                                "order/bulk",
+                                "order/cancelAllAfter",
+                                "order/closePosition",
+                                "position/isolate",
+                                "position/leverage",
+                                "position/riskLimit",
+                                "position/transferMargin",
+                                "user/cancelWithdrawal",
+                                "user/confirmEmail",
+                                "user/confirmEnableTFA",
+                                "user/confirmWithdrawal",
+                                "user/disableTFA",
+                                "user/logout",
+                                "user/logoutAll",
+                                "user/preferences",
+                                "user/requestEnableTFA",
+                                "user/requestWithdrawal"
+                            ],
+                            "put": [
+                                "order",
+                                "order/bulk",
+                                "user"
+                            ],
+                            "delete": [
+                                "apiKey",
+                                "order",
+                                "order/all"
+                            ]
+                        }
+                    },
+                    "exceptions": {
+                        "exact": {
+                            "Invalid API Key.": "AuthenticationError",
+                            "Signature not valid.": "AuthenticationError",
+                            "Access Denied": "PermissionDenied",
+                            "Not Found": "OrderNotFound"
+                        },
+                        "broad": {
+                            "overloaded": "ExchangeNotAvailable",
+                            "Account has insufficient Available Balance": "InsufficientFunds",
+                            "Unable to cancel order due to existing state": "OrderNotFound",
+                            "Invalid orderID": "OrderNotFound",
+                            "This request has expired": "InvalidNonce",
+                            "Invalid ordStatus": "InvalidOrder",
+                            "Invalid price": "InvalidOrder",
+                            "Invalid orderQty": "InvalidOrder"
                         }
                     },
                     "commonCurrencies": {
@@ -66,10 +214,150 @@ impl Bitmex {
         let connector = HttpConnector::new();
         let mut exchange = unsafe {BITMEX_EXCHANGE.as_ref().unwrap().clone()};
         exchange.set_connector(Box::new(connector));
+        exchange.set_sign_hook(Self::sign);
         let mut exchange = Bitmex { exchange };
         Box::from(exchange.fetch_markets().and_then(|_| ok(exchange)))
     }
 
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.exchange.set_credentials(credentials);
+    }
+
+    ///
+    /// Liquidation orders currently being processed
+    /// 
+    pub fn fetch_liquidations(&self, symbol: Option<&str>) -> CCXTFut<Vec<Liquidation>> {
+        let mut params = Vec::new();
+        if let Some(symbol) = symbol {
+            params.push(format!("symbol={}", try_future_box!(self.exchange.market(symbol)).id));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "liquidation", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut liquidations = Vec::new();
+                for liquidation in as_array!(json, "liquidations")? {
+                    liquidations.push(Liquidation {
+                        order_id: String::from(as_str!(liquidation["orderID"], "liquidation->orderID")?),
+                        symbol: symbol_from_id(markets, as_str!(liquidation["symbol"], "liquidation->symbol")?),
+                        side: as_str!(liquidation["side"], "liquidation->side")?.parse()?,
+                        price: safe_f64!(liquidation["price"]),
+                        remaining: safe_f64!(liquidation["leavesQty"]).unwrap_or(0.0),
+                        info: Some(liquidation.clone()),
+                    });
+                }
+                Ok(liquidations)
+            }))
+    }
+
+    ///
+    /// History of the insurance fund sorted by timestamp, the latest entries when since is not given
+    /// 
+    pub fn fetch_insurance_fund(&self, since: Option<i64>, limit: Option<i64>) -> CCXTFut<Vec<InsuranceFund>> {
+        let mut params = vec![String::from("currency=XBt")];
+        match since {
+            Some(since) => params.push(format!("startTime={}", iso8601(since))),
+            None => params.push(String::from("reverse=true")),
+        }
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "insurance", &params)
+            .and_then(|json| {
+                let mut funds = Vec::new();
+                for fund in as_array!(json, "insurance")? {
+                    funds.push(Self::parse_insurance_fund(fund)?);
+                }
+                funds.sort_by_key(|fund| fund.timestamp);
+                Ok(funds)
+            }))
+    }
+
+    ///
+    /// Settlements sorted by timestamp, the latest ones when since is not given
+    /// 
+    pub fn fetch_settlements(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> CCXTFut<Vec<Settlement>> {
+        let mut params = Vec::new();
+        if let Some(symbol) = symbol {
+            params.push(format!("symbol={}", try_future_box!(self.exchange.market(symbol)).id));
+        }
+        match since {
+            Some(since) => params.push(format!("startTime={}", iso8601(since))),
+            None => params.push(String::from("reverse=true")),
+        }
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "settlement", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut settlements = Vec::new();
+                for settlement in as_array!(json, "settlements")? {
+                    settlements.push(Self::parse_settlement(settlement, markets)?);
+                }
+                settlements.sort_by_key(|settlement| settlement.timestamp);
+                Ok(settlements)
+            }))
+    }
+
+    ///
+    /// 24h volume and open interest by root symbol
+    /// 
+    pub fn fetch_exchange_stats(&self) -> CCXTFut<Vec<ExchangeStats>> {
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(get_api!(self.exchange, "public", "stats")
+            .and_then(move |json| {
+                let mut stats = Vec::new();
+                for stat in as_array!(json, "stats")? {
+                    let currency = stat["currency"].as_str().map(|currency| common_currency_code(&currencies, &currency.to_uppercase()));
+                    let scale = if currency.as_ref().map(String::as_str) == Some("BTC") { 0.00000001 } else { 1.0 };
+                    stats.push(ExchangeStats {
+                        root_symbol: String::from(as_str!(stat["rootSymbol"], "stats->rootSymbol")?),
+                        currency,
+                        volume: safe_f64!(stat["volume24h"]),
+                        turnover: safe_f64!(stat["turnover24h"]).map(|turnover| turnover * scale),
+                        open_interest: safe_f64!(stat["openInterest"]),
+                        open_value: safe_f64!(stat["openValue"]).map(|value| value * scale),
+                        info: Some(stat.clone()),
+                    });
+                }
+                Ok(stats)
+            }))
+    }
+
+    ///
+    /// Private requests are signed with HMAC-SHA256(secret, verb + path + expires + body)
+    /// See https://www.bitmex.com/app/apiKeysUsage
+    /// 
+    fn sign(exchange: &Exchange<HttpConnector>, api: &str, request: &mut Request) -> Result<(), Error> {
+        if api != "private" {
+            return Ok(());
+        }
+        exchange.check_required_credentials()?;
+        let credentials = exchange.credentials();
+        let api_key = credentials.api_key.clone().unwrap_or_default();
+        let secret = credentials.secret.clone().unwrap_or_default();
+        let expires = milliseconds() / 1000 + 5;
+        let signature = Self::signature(&secret, request.method_name(), &request.path_and_query(), expires, &request.body().encode());
+        request.add_header("api-expires", expires.to_string().as_str());
+        request.add_header("api-key", api_key.as_str());
+        request.add_header("api-signature", signature.as_str());
+        Ok(())
+    }
+
+    ///
+    /// HMAC-SHA256(secret, verb + path with query + expires + body), hex encoded
+    /// 
+    fn signature(secret: &str, verb: &str, path: &str, expires: i64, body: &str) -> String {
+        hmac_sha256_hex(secret.as_bytes(), format!("{}{}{}{}", verb, path, expires, body).as_bytes())
+    }
+
     fn time_frame(time: CandleTime) -> &'static str {
         match time {
             CandleTime::_5M => "5m",
@@ -79,6 +367,474 @@ impl Bitmex {
         }
     }
 
+    fn parse_trade(trade: &Value, symbol: String) -> Result<Trade, Error> {
+        let timestamp = parse8601(as_str!(trade["timestamp"], "trade->timestamp")?).ok_or(CCXTError::BadResponse)?;
+        let price = as_f64!(trade["price"], "trade->price")?;
+        let amount = as_f64!(trade["size"], "trade->size")?;
+        Ok(Trade {
+            id: safe_string!(trade["trdMatchID"]),
+            timestamp,
+            symbol,
+            order: None,
+            side: trade["side"].as_str().and_then(|side| side.parse().ok()),
+            taker_or_maker: None,
+            price,
+            amount,
+            cost: price * amount,
+            fee: None,
+            info: Some(trade.clone()),
+        })
+    }
+
+    ///
+    /// GET order, the filter is a json object (ex: {"open": true}) url encoded in the query
+    /// 
+    fn fetch_orders_with_filter(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>, filter: Option<Value>) -> FetchOrdersResult {
+        let mut params = Vec::new();
+        if let Some(symbol) = symbol {
+            params.push(format!("symbol={}", try_future_box!(self.exchange.market(symbol)).id));
+        }
+        match since {
+            Some(since) => params.push(format!("startTime={}", iso8601(since))),
+            None => params.push(String::from("reverse=true")),
+        }
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        if let Some(filter) = filter {
+            params.push(format!("filter={}", url_encode(&filter.to_string())));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let symbol = symbol.map(String::from);
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Get, "order", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_orders(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?, symbol.as_ref().map(String::as_str), since, limit)
+            }))
+    }
+
+    fn parse_orders(json: &Value, markets: &HashMap<String, Market>, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> Result<Vec<Order>, Error> {
+        let mut orders = Vec::new();
+        for order in as_array!(json, "orders")? {
+            orders.push(Self::parse_order(order, markets)?);
+        }
+        orders.sort_by_key(|order| order.timestamp);
+        Ok(filter_by_symbol_since_limit(orders, symbol, since, limit))
+    }
+
+    ///
+    /// Map the FIX ordStatus values, orders still working or waiting for a trigger are open
+    /// Unknown statuses are reported as open rather than failing the whole order list
+    /// 
+    fn parse_order_status(status: &str) -> OrderStatus {
+        match status.to_lowercase().as_ref() {
+            "filled" => OrderStatus::Closed,
+            "canceled" => OrderStatus::Canceled,
+            "rejected" => OrderStatus::Rejected,
+            "expired" => OrderStatus::Expired,
+            //New, PartiallyFilled, PendingNew, PendingCancel, PendingReplace, DoneForDay, Stopped, Suspended, Untriggered, Triggered...
+            _ => OrderStatus::Open,
+        }
+    }
+
+    ///
+    /// Parse the accounts of user/margin, the available margin is the free balance
+    /// 
+    fn parse_balance(json: Value, currencies: &HashMap<String, String>) -> Result<Balance, Error> {
+        let mut balance = Balance::default();
+        for account in as_array!(json, "balance")? {
+            let currency = as_str!(account["currency"], "balance->currency")?.to_uppercase();
+            let code = common_currency_code(currencies, &currency);
+            //Balances are in the smallest unit (satoshis for XBt)
+            let scale = Self::wallet_scale(&code);
+            let free = safe_f64!(account["availableMargin"]).map(|free| free * scale);
+            let total = safe_f64!(account["marginBalance"]).map(|total| total * scale);
+            balance.currencies.insert(code, CurrencyBalance::new(free, None, total));
+        }
+        balance.info = Some(json);
+        Ok(balance)
+    }
+
+    fn parse_order(order: &Value, markets: &HashMap<String, Market>) -> Result<Order, Error> {
+        let id = as_str!(order["symbol"], "order->symbol")?;
+        let symbol = symbol_from_id(markets, id);
+        let inverse = market_by_id(markets, id).and_then(|market| market.linear) == Some(false);
+        let price = safe_f64!(order["price"]);
+        let amount = safe_f64!(order["orderQty"]).unwrap_or(0.0);
+        let filled = safe_f64!(order["cumQty"]).unwrap_or(0.0);
+        //Quantities of inverse contracts are in quote currency, their cost is in base currency
+        let cost = match safe_f64!(order["avgPx"]) {
+            Some(average) if inverse && average > 0.0 => filled / average,
+            Some(average) => filled * average,
+            None => 0.0,
+        };
+        Ok(Order {
+            id: String::from(as_str!(order["orderID"], "order->orderID")?),
+            client_order_id: safe_string!(order["clOrdID"]).filter(|id| !id.is_empty()),
+            timestamp: order["timestamp"].as_str().and_then(parse8601),
+            last_trade_timestamp: order["transactTime"].as_str().and_then(parse8601),
+            symbol,
+            order_type: as_str!(order["ordType"], "order->ordType")?.parse()?,
+            side: as_str!(order["side"], "order->side")?.parse()?,
+            price,
+            amount,
+            filled,
+            remaining: (amount - filled).max(0.0),
+            cost,
+            status: Self::parse_order_status(as_str!(order["ordStatus"], "order->ordStatus")?),
+            fee: None,
+            trades: Vec::new(),
+            info: Some(order.clone()),
+        })
+    }
+
+    ///
+    /// Parse an execution of execution/tradeHistory, fees are paid in the settlement currency (satoshis for XBt, micro USDT for USDt)
+    /// 
+    fn parse_execution(execution: &Value, markets: &HashMap<String, Market>, currencies: &HashMap<String, String>) -> Result<Trade, Error> {
+        let symbol = symbol_from_id(markets, as_str!(execution["symbol"], "execution->symbol")?);
+        let timestamp = parse8601(as_str!(execution["timestamp"], "execution->timestamp")?).ok_or(CCXTError::BadResponse)?;
+        let price = as_f64!(execution["lastPx"], "execution->lastPx")?;
+        let amount = as_f64!(execution["lastQty"], "execution->lastQty")?;
+        let fee = match (safe_f64!(execution["execComm"]), execution["settlCurrency"].as_str()) {
+            (Some(cost), Some(currency)) => {
+                let code = common_currency_code(currencies, &currency.to_uppercase());
+                Some(Fee {
+                    cost: cost * Self::wallet_scale(&code),
+                    currency: Some(code),
+                    rate: safe_f64!(execution["commission"]),
+                })
+            },
+            _ => None,
+        };
+        Ok(Trade {
+            id: safe_string!(execution["execID"]),
+            timestamp,
+            symbol,
+            order: safe_string!(execution["orderID"]),
+            side: execution["side"].as_str().and_then(|side| side.parse().ok()),
+            taker_or_maker: match execution["lastLiquidityInd"].as_str() {
+                Some("AddedLiquidity") => Some(TakerOrMaker::Maker),
+                Some("RemovedLiquidity") => Some(TakerOrMaker::Taker),
+                _ => None,
+            },
+            price,
+            amount,
+            //Quantities of inverse contracts are in quote currency
+            cost: safe_f64!(execution["foreignNotional"]).map_or(price * amount, f64::abs),
+            fee,
+            info: Some(execution.clone()),
+        })
+    }
+
+    ///
+    /// Wallet currency of a unified code, wallet amounts are in satoshis (XBt) and micro USDT (USDt)
+    /// 
+    fn wallet_currency(code: Option<&str>) -> Result<&'static str, Error> {
+        match code {
+            None => Ok("all"),
+            Some("BTC") => Ok("XBt"),
+            Some("USDT") => Ok("USDt"),
+            Some(_) => Err(CCXTError::NotSupported.into()),
+        }
+    }
+
+    fn wallet_scale(code: &str) -> f64 {
+        match code {
+            "BTC" => 0.00000001,
+            "USDT" => 0.000001,
+            _ => 1.0,
+        }
+    }
+
+    ///
+    /// Page through user/walletHistory (newest first) with count/start
+    /// Without since only the newest limit entries are fetched, otherwise pages are fetched until since is reached
+    /// 
+    fn fetch_wallet_history(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> CCXTFut<Vec<Value>> {
+        const PAGE_SIZE: i64 = 500;
+        let currency = try_future_box!(Self::wallet_currency(code));
+        let count = if since.is_some() { PAGE_SIZE } else { limit.unwrap_or(100) };
+        let exchange = self.exchange.clone();
+        Box::new(loop_fn(Vec::<Value>::new(), move |mut history| {
+            let currency = format!("currency={}", currency);
+            let count_param = format!("count={}", count);
+            let start = format!("start={}", history.len());
+            get_api!(exchange, "private", "user/walletHistory", currency.as_str(), count_param.as_str(), start.as_str())
+                .and_then(move |json| {
+                    let page = as_array!(json, "walletHistory")?;
+                    let last_page = (page.len() as i64) < count;
+                    let reached_since = since.map_or(true, |since| page.iter()
+                        .filter_map(|entry| entry["transactTime"].as_str().or_else(|| entry["timestamp"].as_str()).and_then(parse8601))
+                        .any(|timestamp| timestamp < since));
+                    history.extend(page.iter().cloned());
+                    if last_page || reached_since {
+                        Ok(Loop::Break(history))
+                    } else {
+                        Ok(Loop::Continue(history))
+                    }
+                })
+        }))
+    }
+
+    ///
+    /// Parse a deposit or withdrawal of user/walletHistory, amounts are in the smallest unit (see wallet_scale)
+    /// 
+    fn parse_transaction(transaction: &Value, transaction_type: TransactionType, currencies: &HashMap<String, String>) -> Result<Transaction, Error> {
+        let currency = common_currency_code(currencies, &as_str!(transaction["currency"], "transaction->currency")?.to_uppercase());
+        let scale = Self::wallet_scale(&currency);
+        let timestamp = transaction["transactTime"].as_str().or_else(|| transaction["timestamp"].as_str()).and_then(parse8601);
+        let status = match transaction["transactStatus"].as_str() {
+            Some("Completed") => TransactionStatus::Ok,
+            Some("Canceled") => TransactionStatus::Canceled,
+            Some("Rejected") => TransactionStatus::Failed,
+            _ => TransactionStatus::Pending,
+        };
+        Ok(Transaction {
+            id: String::from(as_str!(transaction["transactID"], "transaction->transactID")?),
+            txid: safe_string!(transaction["tx"]).filter(|tx| !tx.is_empty()),
+            timestamp,
+            updated: transaction["timestamp"].as_str().and_then(parse8601),
+            address: safe_string!(transaction["address"]).filter(|address| !address.is_empty()),
+            tag: None,
+            transaction_type,
+            amount: as_f64!(transaction["amount"], "transaction->amount")?.abs() * scale,
+            status,
+            fee: safe_f64!(transaction["fee"]).map(|cost| Fee {
+                cost: cost * scale,
+                currency: Some(currency.clone()),
+                rate: None,
+            }),
+            currency,
+            info: Some(transaction.clone()),
+        })
+    }
+
+    ///
+    /// Parse an entry of user/walletHistory, walletBalance is the balance after the change
+    /// 
+    fn parse_ledger_entry(entry: &Value, currencies: &HashMap<String, String>) -> Result<LedgerEntry, Error> {
+        let currency = common_currency_code(currencies, &as_str!(entry["currency"], "ledger->currency")?.to_uppercase());
+        let scale = Self::wallet_scale(&currency);
+        let amount = as_f64!(entry["amount"], "ledger->amount")? * scale;
+        let after = safe_f64!(entry["walletBalance"]).map(|balance| balance * scale);
+        let entry_type = match as_str!(entry["transactType"], "ledger->transactType")? {
+            "Deposit" | "Withdrawal" => LedgerEntryType::Transaction,
+            "RealisedPNL" | "UnrealisedPNL" => LedgerEntryType::Margin,
+            "Transfer" => LedgerEntryType::Transfer,
+            "AffiliatePayout" => LedgerEntryType::Referral,
+            other => LedgerEntryType::Other(String::from(other)),
+        };
+        let status = match entry["transactStatus"].as_str() {
+            Some("Completed") => TransactionStatus::Ok,
+            Some("Canceled") => TransactionStatus::Canceled,
+            Some("Rejected") => TransactionStatus::Failed,
+            _ => TransactionStatus::Pending,
+        };
+        Ok(LedgerEntry {
+            id: String::from(as_str!(entry["transactID"], "ledger->transactID")?),
+            timestamp: entry["transactTime"].as_str().or_else(|| entry["timestamp"].as_str()).and_then(parse8601),
+            direction: if amount < 0.0 { LedgerDirection::Out } else { LedgerDirection::In },
+            account: safe_string!(entry["account"]),
+            reference_id: safe_string!(entry["tx"]).filter(|tx| !tx.is_empty()),
+            reference_account: safe_string!(entry["address"]).filter(|address| !address.is_empty()),
+            entry_type,
+            amount: amount.abs(),
+            before: after.map(|after| after - amount),
+            after,
+            status: Some(status),
+            fee: safe_f64!(entry["fee"]).map(|cost| Fee {
+                cost: cost * scale,
+                currency: Some(currency.clone()),
+                rate: None,
+            }),
+            currency,
+            info: Some(entry.clone()),
+        })
+    }
+
+    ///
+    /// Parse a position, XBt values (pnl, margin, mark value) are in satoshis
+    /// leverage is reported as 100 for cross margin positions
+    /// 
+    fn parse_position(position: &Value, markets: &HashMap<String, Market>) -> Result<Position, Error> {
+        let symbol = symbol_from_id(markets, as_str!(position["symbol"], "position->symbol")?);
+        let scale = if position["currency"].as_str() == Some("XBt") { 0.00000001 } else { 1.0 };
+        let contracts = safe_f64!(position["currentQty"]).unwrap_or(0.0);
+        let side = if contracts > 0.0 {
+            Some(PositionSide::Long)
+        } else if contracts < 0.0 {
+            Some(PositionSide::Short)
+        } else {
+            None
+        };
+        let cross = position["crossMargin"].as_bool().unwrap_or(false);
+        Ok(Position {
+            symbol,
+            timestamp: position["timestamp"].as_str().and_then(parse8601),
+            side,
+            contracts: contracts.abs(),
+            entry_price: safe_f64!(position["avgEntryPrice"]),
+            mark_price: safe_f64!(position["markPrice"]),
+            liquidation_price: safe_f64!(position["liquidationPrice"]),
+            leverage: if cross { None } else { safe_f64!(position["leverage"]) },
+            notional: safe_f64!(position["markValue"]).map(|value| value.abs() * scale),
+            margin: safe_f64!(position["maintMargin"]).map(|margin| margin * scale),
+            unrealized_pnl: safe_f64!(position["unrealisedPnl"]).map(|pnl| pnl * scale),
+            realized_pnl: safe_f64!(position["realisedPnl"]).map(|pnl| pnl * scale),
+            info: Some(position.clone()),
+        })
+    }
+
+    ///
+    /// Instruments of instrument/activeAndIndices, the type is taken from the typ class (swap, future, option, index)
+    /// Inverse contracts (isInverse) are not linear
+    /// 
+    fn parse_markets(re: Value, currencies: &HashMap<String, String>) -> Result<HashMap<String, Market>, Error> {
+        let mut markets = HashMap::<String, Market>::new();
+        for market in as_array!(re, "markets")?.into_iter() {
+            try_block!({
+                let id: String = as_str!(market["symbol"], "market->symbol")?.into();
+                let base_id = as_str!(market["underlying"], "market->base_id")?;
+                let quote_id = as_str!(market["quoteCurrency"], "market->quote_id")?;
+                let base = common_currency_code(currencies, base_id);
+                let quote = common_currency_code(currencies, quote_id);
+                let basequote = format!("{}{}", base_id, quote_id);
+                let symbol = if id == basequote { format!("{}/{}", base, quote) } else { id.clone() };
+                //Instrument class, see the typ field of https://www.bitmex.com/api/explorer/#!/Instrument
+                let typ = as_str!(market["typ"], "market->typ")?;
+                let market_type = if typ.starts_with("FFW") {
+                    MarketType::Swap
+                } else if typ.starts_with("FF") {
+                    MarketType::Future
+                } else if typ.starts_with('O') {
+                    MarketType::Option
+                } else if typ.starts_with('M') {
+                    MarketType::Index
+                } else {
+                    MarketType::Spot
+                };
+                let derivative = market_type != MarketType::Spot && market_type != MarketType::Index;
+                let inverse = market["isInverse"].as_bool().unwrap_or(false);
+                markets.insert(symbol.clone(), Market {
+                    id,
+                    symbol,
+                    base,
+                    quote,
+                    base_id: base_id.into(),
+                    quote_id: quote_id.into(),
+                    active: as_str!(market["state"], "market->state")? != "Unlisted",
+                    precision: (0.0, 0.0),
+                    limits: MarketLimits::new((0.0, 0.0), (0.0, 0.0), (0.0, 0.0)),
+                    market_type,
+                    linear: if derivative { Some(!inverse) } else { None },
+                    settle: market["settlCurrency"].as_str()
+                        .filter(|settle| derivative && !settle.is_empty())
+                        .map(|settle| common_currency_code(currencies, &settle.to_uppercase())),
+                    //Inverse contracts are worth one quote unit, the multiplier of the other ones is in satoshis
+                    contract_size: if !derivative {
+                        None
+                    } else if inverse {
+                        Some(1.0)
+                    } else {
+                        safe_f64!(market["multiplier"]).map(|multiplier| multiplier.abs() * 0.00000001)
+                    },
+                    expiry: market["expiry"].as_str().and_then(parse8601),
+                    underlying_index: safe_string!(market["referenceSymbol"]),
+                    info: None,
+                });
+            });
+        }
+        Ok(markets)
+    }
+
+    ///
+    /// Insurance fund balances are in satoshis
+    /// 
+    fn parse_insurance_fund(fund: &Value) -> Result<InsuranceFund, Error> {
+        Ok(InsuranceFund {
+            currency: String::from("BTC"),
+            timestamp: parse8601(as_str!(fund["timestamp"], "insurance->timestamp")?).ok_or(CCXTError::BadResponse)?,
+            balance: as_f64!(fund["walletBalance"], "insurance->walletBalance")? * 0.00000001,
+            info: Some(fund.clone()),
+        })
+    }
+
+    fn parse_settlement(settlement: &Value, markets: &HashMap<String, Market>) -> Result<Settlement, Error> {
+        Ok(Settlement {
+            symbol: symbol_from_id(markets, as_str!(settlement["symbol"], "settlement->symbol")?),
+            timestamp: parse8601(as_str!(settlement["timestamp"], "settlement->timestamp")?).ok_or(CCXTError::BadResponse)?,
+            settlement_type: String::from(as_str!(settlement["settlementType"], "settlement->settlementType")?),
+            settled_price: safe_f64!(settlement["settledPrice"]),
+            info: Some(settlement.clone()),
+        })
+    }
+
+    ///
+    /// Intervals are sent as a time after the epoch (ex: 2000-01-01T08:00:00.000Z for 8 hours)
+    /// 
+    fn parse_funding_interval(interval: &Value) -> Option<i64> {
+        let epoch = parse8601("2000-01-01T00:00:00.000Z")?;
+        interval.as_str().and_then(parse8601).map(|interval| interval - epoch)
+    }
+
+    ///
+    /// Buckets of trade/bucketed, timestamps are returned in seconds
+    /// 
+    fn parse_ohlcv(json: &Value) -> Result<Vec<Ohlcv>, Error> {
+        let mut ohlcv = Vec::<Ohlcv>::new();
+        for elem in as_array!(json, "ohlcv->timestamp")? {
+            let time =as_str!(elem["timestamp"], "ohlcv->timestamp")?;
+            let timestamp = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S.000Z")?;
+            let open = as_f64!(elem["open"], "ohlcv->open")?;
+            let highest = as_f64!(elem["high"], "ohlcv->high")?;
+            let lowest = as_f64!(elem["low"], "ohlcv->low")?;
+            let losing = as_f64!(elem["close"], "ohlcv->close")?;
+            let volume = as_f64!(elem["volume"], "ohlcv->volume")?;
+            ohlcv.push(Ohlcv{
+                timestamp: timestamp.timestamp(),
+                open,
+                highest,
+                lowest,
+                losing,
+                volume,
+            })
+        }
+        Ok(ohlcv)
+    }
+
+    fn parse_ticker(ticker: &Value, symbol: String) -> Ticker {
+        let timestamp = ticker["timestamp"].as_str().and_then(parse8601).unwrap_or_else(milliseconds);
+        let open = safe_f64!(ticker["prevPrice24h"]);
+        let last = safe_f64!(ticker["lastPrice"]);
+        let (change, average) = match (open, last) {
+            (Some(open), Some(last)) => (Some(last - open), Some((open + last) / 2.0)),
+            _ => (None, None),
+        };
+        Ticker {
+            symbol,
+            timestamp,
+            high: safe_f64!(ticker["highPrice"]),
+            low: safe_f64!(ticker["lowPrice"]),
+            bid: safe_f64!(ticker["bidPrice"]),
+            bid_volume: None,
+            ask: safe_f64!(ticker["askPrice"]),
+            ask_volume: None,
+            vwap: safe_f64!(ticker["vwap"]),
+            open,
+            close: last,
+            last,
+            previous_close: safe_f64!(ticker["prevClosePrice"]),
+            change,
+            percentage: safe_f64!(ticker["lastChangePcnt"]).map(|percentage| percentage * 100.0),
+            average,
+            base_volume: safe_f64!(ticker["homeNotional24h"]),
+            quote_volume: safe_f64!(ticker["foreignNotional24h"]),
+            info: Some(ticker.clone()),
+        }
+    }
+
 }
 
 impl ExchangeTrait for Bitmex {
@@ -88,61 +844,391 @@ impl ExchangeTrait for Bitmex {
         let bin_size = format!("binSize={}", Self::time_frame(timeframe));
         let symbol = format!("symbol={}", market.id);
         let count = format!("count={}", limit);
-        let date = format!("{}", NaiveDateTime::from_timestamp(since, 0).format("%Y-%m-%dT%H:%M:%S"));
+        let date = format!("startTime={}", NaiveDateTime::from_timestamp(since, 0).format("%Y-%m-%dT%H:%M:%S"));
         Box::from(get_api!(self.exchange, "public", "trade/bucketed", bin_size.as_str(), symbol.as_str(), count.as_str(), date.as_str())
-        .and_then(move |json| {
-            let mut ohlcv = Vec::<Ohlcv>::new();
-            try_block!({
-                for elem in as_array!(json, "ohlcv->timestamp")? {
-                    let time =as_str!(elem["timestamp"], "ohlcv->timestamp")?;
-                    let timestamp = NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S.000Z")?;
-                    let open = as_f64!(elem["open"], "ohlcv->open")?;
-                    let highest = as_f64!(elem["high"], "ohlcv->high")?;
-                    let lowest = as_f64!(elem["low"], "ohlcv->low")?;
-                    let losing = as_f64!(elem["close"], "ohlcv->close")?;
-                    let volume = as_f64!(elem["volume"], "ohlcv->volume")?;
-                    ohlcv.push(Ohlcv{
-                        timestamp: timestamp.timestamp(),
-                        open,
-                        highest,
-                        lowest,
-                        losing,
-                        volume,
-                    })
+        .and_then(move |json| Self::parse_ohlcv(&json)))
+    }
+
+    fn fetch_ticker(&self, symbol: &str) -> FetchTickerResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        if !market.active {
+            return Box::new(err(CCXTError::ExchangeError.into()));
+        }
+        let id = format!("symbol={}", market.id);
+        Box::from(get_api!(self.exchange, "public", "instrument", id.as_str())
+            .and_then(move |json| {
+                let ticker = as_array!(json, "ticker")?.first().ok_or(CCXTError::NullResponse)?;
+                Ok(Self::parse_ticker(ticker, market.symbol))
+            }))
+    }
+
+    fn fetch_tickers(&self, symbols: Option<&[&str]>) -> FetchTickersResult {
+        let symbols: Option<Vec<String>> = symbols.map(|symbols| symbols.iter().map(|symbol| String::from(*symbol)).collect());
+        let lock = self.exchange.market.clone();
+        Box::from(get_api!(self.exchange, "public", "instrument/active")
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut tickers = HashMap::<String, Ticker>::new();
+                for ticker in as_array!(json, "tickers")? {
+                    let id = as_str!(ticker["symbol"], "ticker->symbol")?;
+                    if let Some(market) = markets.values().find(|market| market.id == id) {
+                        if symbols.as_ref().map_or(true, |symbols| symbols.contains(&market.symbol)) {
+                            tickers.insert(market.symbol.clone(), Self::parse_ticker(ticker, market.symbol.clone()));
+                        }
+                    }
                 }
-            });
-            ok(ohlcv)
-        }))
+                Ok(tickers)
+            }))
     }
 
-    fn fetch_markets(&mut self) -> LoadMarketResult {
-        fn parse_markets(re: Value) -> Result<HashMap<String, Market>, Error> {
-            let mut markets = HashMap::<String, Market>::new();
-            for market in as_array!(re, "markets")?.into_iter() {
-                try_block!({
-                    let id: String = as_str!(market["symbol"], "market->symbol")?.into();
-                    let base_id = as_str!(market["underlying"], "market->base_id")?;
-                    let quote_id = as_str!(market["quoteCurrency"], "market->quote_id")?;
-                    let basequote = format!("{}{}", base_id, quote_id);
-                    let symbol = if id == basequote { format!("{}/{}", base_id, quote_id) } else { id.clone() };
-                    markets.insert(symbol.clone(), Market {
-                        id,
-                        symbol,
-                        base_id: base_id.into(),
-                        quote_id: quote_id.into(),
-                        active: as_str!(market["state"], "market->state")? != "Unlisted",
-                        precision: (0.0, 0.0),
-                        limits: MarketLimits::new((0.0, 0.0), (0.0, 0.0), (0.0, 0.0)),
-                        info: None,
+    fn fetch_order_book(&self, symbol: &str, limit: Option<i64>) -> FetchOrderBookResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let id = format!("symbol={}", market.id);
+        let depth = format!("depth={}", limit.unwrap_or(0));
+        Box::from(get_api!(self.exchange, "public", "orderBook/L2", id.as_str(), depth.as_str())
+            .and_then(move |json| {
+                let mut bids = Vec::new();
+                let mut asks = Vec::new();
+                for order in as_array!(json, "orderbook")? {
+                    let level = (as_f64!(order["price"], "orderbook->price")?, as_f64!(order["size"], "orderbook->size")?);
+                    match as_str!(order["side"], "orderbook->side")? {
+                        "Sell" => asks.push(level),
+                        _ => bids.push(level),
+                    }
+                }
+                Ok(OrderBook::new(bids, asks, None))
+            }))
+    }
+
+    fn fetch_trades(&self, symbol: &str, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("symbol={}", market.id)];
+        match since {
+            Some(since) => params.push(format!("startTime={}", iso8601(since))),
+            None => params.push(String::from("reverse=true")),
+        }
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "trade", &params)
+            .and_then(move |json| {
+                let mut trades = Vec::new();
+                for trade in as_array!(json, "trades")? {
+                    trades.push(Self::parse_trade(trade, market.symbol.clone())?);
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(trades)
+            }))
+    }
+
+    fn fetch_my_trades(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let mut params = Vec::new();
+        if let Some(symbol) = symbol {
+            params.push(format!("symbol={}", try_future_box!(self.exchange.market(symbol)).id));
+        }
+        match since {
+            Some(since) => params.push(format!("startTime={}", iso8601(since))),
+            None => params.push(String::from("reverse=true")),
+        }
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let symbol = symbol.map(String::from);
+        let lock = self.exchange.market.clone();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Get, "execution/tradeHistory", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut trades = Vec::new();
+                for execution in as_array!(json, "executions")? {
+                    //Funding and settlement executions are not trades
+                    if execution["execType"].as_str() == Some("Trade") {
+                        trades.push(Self::parse_execution(execution, markets, &currencies)?);
+                    }
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(filter_by_symbol_since_limit(trades, symbol.as_ref().map(String::as_str), since, limit))
+            }))
+    }
+
+    ///
+    /// Bitmex only support BTC deposits, the address never change
+    /// 
+    fn fetch_deposit_address(&self, code: &str) -> DepositAddressResult {
+        if code != "BTC" {
+            return Box::new(err(CCXTError::NotSupported.into()));
+        }
+        Box::from(get_api!(self.exchange, "private", "user/depositAddress", "currency=XBt")
+            .and_then(|json| {
+                Ok(DepositAddress {
+                    currency: String::from("BTC"),
+                    address: parse_deposit_address(&json)?,
+                    tag: None,
+                    info: Some(json),
+                })
+            }))
+    }
+
+    ///
+    /// BTC only, the amount is sent in satoshis
+    /// 
+    fn withdraw(&self, code: &str, amount: f64, address: &str, _tag: Option<&str>) -> WithdrawResult {
+        try_future_box!(check_address(address));
+        if code != "BTC" {
+            return Box::new(err(CCXTError::NotSupported.into()));
+        }
+        let amount = format!("amount={}", (amount * 100000000.0).round() as i64);
+        let address = format!("address={}", address);
+        Box::from(post_api!(self.exchange, "private", "user/requestWithdrawal", "currency=XBt", amount.as_str(), address.as_str())
+            .and_then(|json| {
+                Ok(WithdrawResponse {
+                    id: String::from(as_str!(json["transactID"], "withdraw->transactID")?),
+                    info: Some(json),
+                })
+            }))
+    }
+
+    ///
+    /// Deposits and withdrawals of user/walletHistory, BTC and USDT wallets
+    /// 
+    fn fetch_transactions(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTransactionsResult {
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.fetch_wallet_history(code, since, limit)
+            .and_then(move |history| {
+                let mut transactions = Vec::new();
+                for transaction in history.iter() {
+                    //Realised pnl, transfers and affiliate payouts are ledger entries
+                    let transaction_type = match transaction["transactType"].as_str() {
+                        Some("Deposit") => TransactionType::Deposit,
+                        Some("Withdrawal") => TransactionType::Withdrawal,
+                        _ => continue,
+                    };
+                    transactions.push(Self::parse_transaction(transaction, transaction_type, &currencies)?);
+                }
+                transactions.sort_by_key(|transaction| transaction.timestamp);
+                Ok(filter_by_currency_since_limit(transactions, None, since, limit))
+            }))
+    }
+
+    ///
+    /// Every entry of user/walletHistory, BTC and USDT wallets
+    /// 
+    fn fetch_ledger(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchLedgerResult {
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.fetch_wallet_history(code, since, limit)
+            .and_then(move |history| {
+                let mut entries = Vec::new();
+                for entry in history.iter() {
+                    entries.push(Self::parse_ledger_entry(entry, &currencies)?);
+                }
+                entries.sort_by_key(|entry| entry.timestamp);
+                Ok(filter_by_currency_since_limit(entries, None, since, limit))
+            }))
+    }
+
+    fn fetch_positions(&self, symbols: Option<&[&str]>) -> FetchPositionsResult {
+        let symbols: Option<Vec<String>> = symbols.map(|symbols| symbols.iter().map(|symbol| String::from(*symbol)).collect());
+        let lock = self.exchange.market.clone();
+        Box::from(get_api!(self.exchange, "private", "position")
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut positions = Vec::new();
+                for position in as_array!(json, "positions")? {
+                    let position = Self::parse_position(position, markets)?;
+                    if symbols.as_ref().map_or(true, |symbols| symbols.contains(&position.symbol)) {
+                        positions.push(position);
+                    }
+                }
+                Ok(positions)
+            }))
+    }
+
+    ///
+    /// A leverage of 0 switch the position to cross margin
+    /// 
+    fn set_leverage(&self, symbol: &str, leverage: f64) -> PositionResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let id = format!("symbol={}", market.id);
+        let leverage = format!("leverage={}", leverage);
+        let lock = self.exchange.market.clone();
+        Box::from(post_api!(self.exchange, "private", "position/leverage", id.as_str(), leverage.as_str())
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_position(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    ///
+    /// From the instrument, timestamp is the next funding time
+    /// 
+    fn fetch_funding_rate(&self, symbol: &str) -> FetchFundingRateResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        if market.market_type != MarketType::Swap {
+            return Box::new(err(CCXTError::NotSupported.into()));
+        }
+        let id = format!("symbol={}", market.id);
+        Box::from(get_api!(self.exchange, "public", "instrument", id.as_str())
+            .and_then(move |json| {
+                let instrument = as_array!(json, "instrument")?.first().ok_or(CCXTError::NullResponse)?;
+                Ok(FundingRate {
+                    symbol: market.symbol,
+                    timestamp: instrument["fundingTimestamp"].as_str().and_then(parse8601),
+                    rate: as_f64!(instrument["fundingRate"], "instrument->fundingRate")?,
+                    predicted_rate: safe_f64!(instrument["indicativeFundingRate"]),
+                    interval: Self::parse_funding_interval(&instrument["fundingInterval"]),
+                    info: Some(instrument.clone()),
+                })
+            }))
+    }
+
+    fn fetch_funding_history(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchFundingHistoryResult {
+        let mut params = Vec::new();
+        if let Some(symbol) = symbol {
+            params.push(format!("symbol={}", try_future_box!(self.exchange.market(symbol)).id));
+        }
+        match since {
+            Some(since) => params.push(format!("startTime={}", iso8601(since))),
+            None => params.push(String::from("reverse=true")),
+        }
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let symbol = symbol.map(String::from);
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "funding", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut rates = Vec::new();
+                for funding in as_array!(json, "funding")? {
+                    rates.push(FundingRate {
+                        symbol: symbol_from_id(markets, as_str!(funding["symbol"], "funding->symbol")?),
+                        timestamp: funding["timestamp"].as_str().and_then(parse8601),
+                        rate: as_f64!(funding["fundingRate"], "funding->fundingRate")?,
+                        predicted_rate: None,
+                        interval: Self::parse_funding_interval(&funding["fundingInterval"]),
+                        info: Some(funding.clone()),
                     });
-                });
-            }
-            Ok(markets)
+                }
+                rates.sort_by_key(|rate| rate.timestamp);
+                Ok(filter_by_symbol_since_limit(rates, symbol.as_ref().map(String::as_str), since, limit))
+            }))
+    }
+
+    fn fetch_currencies(&self) -> FetchCurrenciesResult {
+        Box::new(ok(self.exchange.currencies_from_markets()))
+    }
+
+    fn fetch_balance(&self) -> FetchBalanceResult {
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(get_api!(self.exchange, "private", "user/margin", "currency=all")
+            .and_then(move |json| Self::parse_balance(json, &currencies)))
+    }
+
+    fn create_order(&self, symbol: &str, order_type: OrderType, side: OrderSide, amount: f64, price: Option<f64>) -> OrderResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let priced = order_type == OrderType::Limit || order_type == OrderType::StopLimit;
+        let order_type = match order_type {
+            OrderType::Market => "Market",
+            OrderType::Limit => "Limit",
+            OrderType::Stop => "Stop",
+            OrderType::StopLimit => "StopLimit",
+            OrderType::Other(ref order_type) => order_type.as_str(),
+        };
+        let side = match side {
+            OrderSide::Buy => "Buy",
+            OrderSide::Sell => "Sell",
+        };
+        let mut params = vec![
+            format!("symbol={}", market.id),
+            format!("side={}", side),
+            format!("orderQty={}", amount),
+            format!("ordType={}", order_type),
+        ];
+        //Market and stop orders are rejected when they have a price
+        if let (true, Some(price)) = (priced, price) {
+            params.push(format!("price={}", price));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "order", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_order(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    fn cancel_order(&self, id: &str, _symbol: Option<&str>) -> OrderResult {
+        let id = format!("orderID={}", id);
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Delete, "order", &[id.as_str()])
+            .and_then(move |json| {
+                let order = as_array!(json, "order")?.first().ok_or(CCXTError::OrderNotFound)?;
+                //Orders that can't be canceled are still returned, with the reason in the error field
+                if let Some(error) = order["error"].as_str() {
+                    if error.contains("Unable to cancel order due to existing state") {
+                        return Err(CCXTError::OrderNotFound.into());
+                    }
+                }
+                let markets = lock.read().unwrap();
+                Self::parse_order(order, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    fn has(&self, capability: &str) -> bool {
+        self.exchange.has(capability)
+    }
+
+    ///
+    /// PUT order, the amount is the new total quantity of the order (filled included)
+    /// 
+    fn amend_order(&self, id: &str, _symbol: &str, amount: Option<f64>, price: Option<f64>) -> OrderResult {
+        let mut params = vec![format!("orderID={}", id)];
+        if let Some(amount) = amount {
+            params.push(format!("orderQty={}", amount));
         }
+        if let Some(price) = price {
+            params.push(format!("price={}", price));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Put, "order", &params)
+            .and_then(move |json| {
+                let markets = lock.read().unwrap();
+                Self::parse_order(&json, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    fn fetch_order(&self, id: &str, symbol: Option<&str>) -> OrderResult {
+        Box::from(self.fetch_orders_with_filter(symbol, None, None, Some(serde_json::json!({ "orderID": id })))
+            .and_then(|mut orders| {
+                if orders.len() != 1 {
+                    return Err(CCXTError::OrderNotFound.into());
+                }
+                Ok(orders.remove(0))
+            }))
+    }
+
+    fn fetch_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        self.fetch_orders_with_filter(symbol, since, limit, None)
+    }
+
+    fn fetch_open_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        self.fetch_orders_with_filter(symbol, since, limit, Some(serde_json::json!({ "open": true })))
+    }
+
+    fn fetch_markets(&mut self) -> LoadMarketResult {
         let lock = self.exchange.market.clone();
+        let currencies = self.exchange.common_currencies.clone();
         Box::from(get_api!(self.exchange, "public", "instrument/activeAndIndices")
             .and_then(move |re| {
-                match parse_markets(re) {
+                match Self::parse_markets(re, &currencies) {
                     Ok(result) =>{ 
                         *lock.write().unwrap() = Some(result);
                         ok(lock)
@@ -160,24 +1246,302 @@ impl ExchangeTrait for Bitmex {
 
 #[cfg(test)]
 mod tests {
-    use hyper::rt;
+    use tokio::runtime::current_thread::block_on_all;
     use super::Bitmex;
-    use futures::future;
     use futures::Future;
     //use futures::future::{ok, err};
     use crate::prelude::*;
     use crate::base::exchange::ExchangeTrait;
     #[test]
+    #[ignore]
     fn test_plateform() {
-        rt::run(future::lazy(move||{
-            Bitmex::new().and_then(|exchange| {
-                exchange.fetch_ohlcv("XBT/USD", CandleTime::_1M, 1240020225, 100)
-                    .map(|ohlcv| {
-                        println!("{:?}", ohlcv);
-                    })
-            })
-            .map(|_|{})
-            .map_err(|_|{})
-        }));
+        let ohlcv = block_on_all(Bitmex::new().and_then(|exchange| {
+            exchange.fetch_ohlcv("BTC/USD", CandleTime::_1M, 1540000000, 100)
+        })).unwrap();
+        assert!(!ohlcv.is_empty());
+        assert!(ohlcv.iter().all(|candle| candle.timestamp >= 1540000000 && candle.lowest <= candle.highest));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ticker() {
+        let ticker = block_on_all(Bitmex::new().and_then(|exchange| exchange.fetch_ticker("BTC/USD"))).unwrap();
+        assert_eq!(ticker.symbol, "BTC/USD");
+        assert!(ticker.bid.unwrap() <= ticker.ask.unwrap());
+        assert!(ticker.last.is_some());
+    }
+
+    #[test]
+    fn test_parse_order_status() {
+        assert_eq!(Bitmex::parse_order_status("PartiallyFilled"), OrderStatus::Open);
+        assert_eq!(Bitmex::parse_order_status("PendingCancel"), OrderStatus::Open);
+        assert_eq!(Bitmex::parse_order_status("Untriggered"), OrderStatus::Open);
+        assert_eq!(Bitmex::parse_order_status("Filled"), OrderStatus::Closed);
+        assert_eq!(Bitmex::parse_order_status("Canceled"), OrderStatus::Canceled);
+        assert_eq!(Bitmex::parse_order_status("Rejected"), OrderStatus::Rejected);
+    }
+
+    #[test]
+    fn test_signature() {
+        //Vectors of https://www.bitmex.com/app/apiKeysUsage
+        let secret = "chNOOS4KvNXR_Xq4k4c9qsfoKWvnDecLATCRlcBwyKDYnWgO";
+        assert_eq!(Bitmex::signature(secret, "GET", "/api/v1/instrument", 1518064236, ""),
+            "c7682d435d0cfe87c16098df34ef2eb5a549d4c5a3c2b1f0f77b8af73423bf00");
+        assert_eq!(Bitmex::signature(secret, "GET", "/api/v1/instrument?filter=%7B%22symbol%22%3A+%22XBTM15%22%7D", 1518064237, ""),
+            "e2f422547eecb5b3cb29ade2127e21b858b235b386bfa45e1c1756eb3383919f");
+        assert_eq!(Bitmex::signature(secret, "POST", "/api/v1/order", 1518064238, r#"{"symbol":"XBTM15","price":219.0,"clOrdID":"mm_bitmex_1a/oemUeQ4CAJZgP3fjHsA","orderQty":98}"#),
+            "1749cd2ccae4aa49048ae09f0b95110cee706e0944e6a14ad0b3a8cb45bd336b");
+    }
+
+    #[test]
+    fn test_parse_balance() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"account": 1, "currency": "XBt", "marginBalance": 150000000, "availableMargin": 100000000},
+            {"account": 1, "currency": "USDt", "marginBalance": 20000000, "availableMargin": 5000000}
+        ]"#).unwrap();
+        let mut currencies = std::collections::HashMap::new();
+        currencies.insert(String::from("XBT"), String::from("BTC"));
+        let balance = Bitmex::parse_balance(json, &currencies).unwrap();
+        let btc = balance.get("BTC").unwrap();
+        assert_eq!((btc.free, btc.used, btc.total), (1.0, 0.5, 1.5));
+        let usdt = balance.get("USDT").unwrap();
+        assert_eq!((usdt.free, usdt.used, usdt.total), (5.0, 15.0, 20.0));
+    }
+
+    #[test]
+    fn test_parse_order() {
+        let order: serde_json::Value = serde_json::from_str(r#"{
+            "orderID": "c6a4e9b1-2d1c-4f7e-9a3b-1f0b6c1f2a11", "clOrdID": "", "symbol": "XBTUSD",
+            "side": "Buy", "orderQty": 100, "price": 6500.5, "ordType": "Limit", "ordStatus": "PartiallyFilled",
+            "cumQty": 40, "avgPx": 6400, "leavesQty": 60, "transactTime": "2018-10-20T10:00:01.000Z", "timestamp": "2018-10-20T10:00:00.000Z"
+        }"#).unwrap();
+        let order = Bitmex::parse_order(&order, &std::collections::HashMap::new()).unwrap();
+        assert_eq!(order.symbol, "XBTUSD");
+        assert_eq!(order.cost, 6400.0 * 40.0);
+        let market = Market {
+            id: String::from("XBTUSD"),
+            symbol: String::from("BTC/USD"),
+            base: String::from("BTC"),
+            quote: String::from("USD"),
+            base_id: String::from("XBT"),
+            quote_id: String::from("USD"),
+            active: true,
+            precision: (0.0, 0.5),
+            limits: MarketLimits::new((1.0, 0.0), (0.5, 0.0), (0.0, 0.0)),
+            market_type: MarketType::Swap,
+            linear: Some(false),
+            settle: Some(String::from("BTC")),
+            contract_size: Some(1.0),
+            expiry: None,
+            underlying_index: Some(String::from(".BXBT")),
+            info: None,
+        };
+        let markets = vec![(market.symbol.clone(), market)].into_iter().collect();
+        let order = Bitmex::parse_order(&order.info.unwrap(), &markets).unwrap();
+        assert_eq!(order.symbol, "BTC/USD");
+        assert_eq!(order.client_order_id, None);
+        assert_eq!(order.order_type, OrderType::Limit);
+        assert_eq!(order.side, OrderSide::Buy);
+        assert_eq!(order.status, OrderStatus::Open);
+        assert_eq!((order.amount, order.filled, order.remaining), (100.0, 40.0, 60.0));
+        assert_eq!(order.cost, 40.0 / 6400.0);
+        assert_eq!(order.timestamp, Some(1540029600000));
+    }
+
+    #[test]
+    fn test_parse_orders() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"orderID": "b", "symbol": "XBTUSD", "side": "Sell", "orderQty": 10, "price": 7000, "ordType": "StopLimit",
+             "ordStatus": "Untriggered", "cumQty": 0, "timestamp": "2018-10-20T10:00:02.000Z"},
+            {"orderID": "a", "symbol": "XBTUSD", "side": "Buy", "orderQty": 10, "price": null, "ordType": "Market",
+             "ordStatus": "Filled", "cumQty": 10, "timestamp": "2018-10-20T10:00:01.000Z"},
+            {"orderID": "c", "symbol": "ETHUSD", "side": "Buy", "orderQty": 1, "price": 200, "ordType": "Limit",
+             "ordStatus": "PendingNew", "cumQty": 0, "timestamp": "2018-10-20T10:00:03.000Z"}
+        ]"#).unwrap();
+        let markets = std::collections::HashMap::new();
+        let orders = Bitmex::parse_orders(&json, &markets, None, None, None).unwrap();
+        assert_eq!(orders.iter().map(|order| order.id.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(orders[0].status, OrderStatus::Closed);
+        assert_eq!(orders[1].order_type, OrderType::StopLimit);
+        assert_eq!(orders[1].status, OrderStatus::Open);
+        let orders = Bitmex::parse_orders(&json, &markets, Some("XBTUSD"), Some(1540029602000), None).unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].id, "b");
+    }
+
+    #[test]
+    fn test_parse_execution() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"execID": "e1", "orderID": "o1", "symbol": "XBTUSD", "side": "Buy", "lastPx": 6500, "lastQty": 650,
+             "foreignNotional": -650, "execComm": 7500, "commission": 0.00075, "settlCurrency": "XBt",
+             "lastLiquidityInd": "RemovedLiquidity", "timestamp": "2018-10-20T10:00:00.000Z"},
+            {"execID": "e2", "orderID": "o2", "symbol": "XBTUSDT", "side": "Sell", "lastPx": 20000, "lastQty": 0.5,
+             "execComm": 2500000, "commission": 0.00025, "settlCurrency": "USDt",
+             "lastLiquidityInd": "AddedLiquidity", "timestamp": "2018-10-20T10:00:01.000Z"}
+        ]"#).unwrap();
+        let markets = std::collections::HashMap::new();
+        let currencies = vec![(String::from("XBT"), String::from("BTC"))].into_iter().collect();
+        let trade = Bitmex::parse_execution(&json[0], &markets, &currencies).unwrap();
+        assert_eq!(trade.taker_or_maker, Some(TakerOrMaker::Taker));
+        assert_eq!(trade.cost, 650.0);
+        let fee = trade.fee.unwrap();
+        assert_eq!(fee.currency, Some(String::from("BTC")));
+        assert_eq!(fee.cost, 7500.0 * 0.00000001);
+        let trade = Bitmex::parse_execution(&json[1], &markets, &currencies).unwrap();
+        assert_eq!(trade.taker_or_maker, Some(TakerOrMaker::Maker));
+        assert_eq!(trade.cost, 20000.0 * 0.5);
+        let fee = trade.fee.unwrap();
+        assert_eq!(fee.currency, Some(String::from("USDT")));
+        assert_eq!(fee.cost, 2500000.0 * 0.000001);
+    }
+
+    #[test]
+    fn test_parse_ticker() {
+        let ticker: serde_json::Value = serde_json::from_str(r#"{
+            "symbol": "XBTUSD", "timestamp": "2018-10-20T10:00:00.000Z", "prevPrice24h": 6400, "lastPrice": 6500,
+            "highPrice": 6550, "lowPrice": 6380, "bidPrice": 6499.5, "askPrice": 6500, "vwap": 6471.2,
+            "prevClosePrice": 6410.3, "lastChangePcnt": 0.0156, "homeNotional24h": 1520.4, "foreignNotional24h": 9839000
+        }"#).unwrap();
+        let ticker = Bitmex::parse_ticker(&ticker, String::from("BTC/USD"));
+        assert_eq!(ticker.symbol, "BTC/USD");
+        assert_eq!(ticker.timestamp, 1540029600000);
+        assert_eq!((ticker.bid, ticker.ask), (Some(6499.5), Some(6500.0)));
+        assert_eq!((ticker.high, ticker.low), (Some(6550.0), Some(6380.0)));
+        assert_eq!((ticker.open, ticker.last), (Some(6400.0), Some(6500.0)));
+        assert_eq!((ticker.change, ticker.average), (Some(100.0), Some(6450.0)));
+        assert_eq!(ticker.percentage, Some(0.0156 * 100.0));
+        assert_eq!((ticker.base_volume, ticker.quote_volume), (Some(1520.4), Some(9839000.0)));
+    }
+
+    #[test]
+    fn test_parse_ohlcv() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"timestamp": "2018-10-20T10:01:00.000Z", "symbol": "XBTUSD", "open": 6500, "high": 6505.5, "low": 6498, "close": 6501, "volume": 120000},
+            {"timestamp": "2018-10-20T10:02:00.000Z", "symbol": "XBTUSD", "open": 6501, "high": 6502, "low": 6490, "close": 6495.5, "volume": 98000}
+        ]"#).unwrap();
+        let ohlcv = Bitmex::parse_ohlcv(&json).unwrap();
+        assert_eq!(ohlcv.len(), 2);
+        assert_eq!(ohlcv[0].timestamp, 1540029660);
+        assert_eq!((ohlcv[0].open, ohlcv[0].highest, ohlcv[0].lowest, ohlcv[0].losing), (6500.0, 6505.5, 6498.0, 6501.0));
+        assert_eq!(ohlcv[1].volume, 98000.0);
+    }
+
+    #[test]
+    fn test_parse_ledger_entry() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"transactID": "t1", "account": 24242, "currency": "XBt", "transactType": "RealisedPNL", "amount": -12345,
+             "fee": 0, "transactStatus": "Completed", "address": "XBTUSD", "tx": "", "walletBalance": 100000000,
+             "transactTime": "2018-10-20T12:00:00.000Z", "timestamp": "2018-10-20T12:00:00.123Z"},
+            {"transactID": "t2", "account": 24242, "currency": "USDt", "transactType": "Deposit", "amount": 5000000,
+             "fee": 1000, "transactStatus": "Pending", "address": "0xabc", "tx": "0xdef", "walletBalance": null,
+             "transactTime": null, "timestamp": "2018-10-21T08:00:00.000Z"}
+        ]"#).unwrap();
+        let currencies = vec![(String::from("XBT"), String::from("BTC"))].into_iter().collect();
+        let entry = Bitmex::parse_ledger_entry(&json[0], &currencies).unwrap();
+        assert_eq!(entry.currency, "BTC");
+        assert_eq!(entry.entry_type, LedgerEntryType::Margin);
+        assert_eq!(entry.direction, LedgerDirection::Out);
+        assert_eq!(entry.amount, 12345.0 * 0.00000001);
+        assert_eq!(entry.after, Some(100000000.0 * 0.00000001));
+        assert_eq!(entry.before, Some(100000000.0 * 0.00000001 + 12345.0 * 0.00000001));
+        assert_eq!(entry.status, Some(TransactionStatus::Ok));
+        assert_eq!(entry.timestamp, Some(1540036800000));
+        assert_eq!(entry.reference_id, None);
+        let entry = Bitmex::parse_ledger_entry(&json[1], &currencies).unwrap();
+        assert_eq!(entry.currency, "USDT");
+        assert_eq!(entry.entry_type, LedgerEntryType::Transaction);
+        assert_eq!(entry.direction, LedgerDirection::In);
+        assert_eq!(entry.amount, 5000000.0 * 0.000001);
+        assert_eq!((entry.before, entry.after), (None, None));
+        assert_eq!(entry.status, Some(TransactionStatus::Pending));
+        assert_eq!(entry.timestamp, Some(1540108800000));
+        assert_eq!(entry.reference_id, Some(String::from("0xdef")));
+        assert_eq!(entry.fee.unwrap().cost, 1000.0 * 0.000001);
+    }
+
+    #[test]
+    fn test_parse_markets() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"symbol": "XBTUSD", "underlying": "XBT", "quoteCurrency": "USD", "typ": "FFWCSX", "state": "Open",
+             "isInverse": true, "settlCurrency": "XBt", "multiplier": -100000000, "expiry": null, "referenceSymbol": ".BXBT"},
+            {"symbol": "XBTZ18", "underlying": "XBT", "quoteCurrency": "USD", "typ": "FFCCSX", "state": "Open",
+             "isInverse": true, "settlCurrency": "XBt", "multiplier": -100000000, "expiry": "2018-12-28T12:00:00.000Z", "referenceSymbol": ".BXBT30M"},
+            {"symbol": "ETHUSD", "underlying": "ETH", "quoteCurrency": "USD", "typ": "FFWCSX", "state": "Open",
+             "isInverse": false, "settlCurrency": "XBt", "multiplier": 100, "expiry": null, "referenceSymbol": ".BETH"},
+            {"symbol": ".BXBT", "underlying": "XBT", "quoteCurrency": "USD", "typ": "MRCXXX", "state": "Unlisted",
+             "isInverse": false, "settlCurrency": "", "multiplier": null, "expiry": null, "referenceSymbol": ".BXBT"}
+        ]"#).unwrap();
+        let currencies = vec![(String::from("XBT"), String::from("BTC"))].into_iter().collect();
+        let markets = Bitmex::parse_markets(json, &currencies).unwrap();
+        assert_eq!(markets.len(), 4);
+        let swap = &markets["BTC/USD"];
+        assert_eq!((swap.market_type, swap.linear, swap.contract_size), (MarketType::Swap, Some(false), Some(1.0)));
+        assert_eq!((swap.settle.as_ref().map(String::as_str), swap.expiry), (Some("BTC"), None));
+        let future = &markets["XBTZ18"];
+        assert_eq!((future.market_type, future.linear), (MarketType::Future, Some(false)));
+        assert_eq!(future.expiry, Some(1545998400000));
+        assert_eq!(future.underlying_index, Some(String::from(".BXBT30M")));
+        let quanto = &markets["ETH/USD"];
+        assert_eq!((quanto.market_type, quanto.linear), (MarketType::Swap, Some(true)));
+        assert_eq!(quanto.contract_size, Some(100.0 * 0.00000001));
+        let index = &markets[".BXBT"];
+        assert_eq!((index.market_type, index.linear, index.settle.clone(), index.contract_size), (MarketType::Index, None, None, None));
+        assert!(!index.active);
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            {"symbol": "XBTUSD", "currency": "XBt", "currentQty": -300, "avgEntryPrice": 6500.5, "markPrice": 6480.2,
+             "liquidationPrice": 100000000, "leverage": 100, "crossMargin": true, "markValue": -4629480,
+             "maintMargin": 22430, "unrealisedPnl": 14070, "realisedPnl": -2175, "timestamp": "2018-10-20T10:00:00.000Z"},
+            {"symbol": "ETHUSD", "currency": "XBt", "currentQty": 10, "avgEntryPrice": 200, "leverage": 5,
+             "crossMargin": false, "markValue": 200000, "timestamp": "2018-10-20T10:00:00.000Z"}
+        ]"#).unwrap();
+        let markets = std::collections::HashMap::new();
+        let position = Bitmex::parse_position(&json[0], &markets).unwrap();
+        assert_eq!(position.symbol, "XBTUSD");
+        assert_eq!((position.side, position.contracts), (Some(PositionSide::Short), 300.0));
+        assert_eq!((position.entry_price, position.mark_price), (Some(6500.5), Some(6480.2)));
+        assert_eq!(position.leverage, None);
+        assert_eq!(position.notional, Some(4629480.0 * 0.00000001));
+        assert_eq!(position.unrealized_pnl, Some(14070.0 * 0.00000001));
+        assert_eq!(position.realized_pnl, Some(-2175.0 * 0.00000001));
+        assert_eq!(position.timestamp, Some(1540029600000));
+        let position = Bitmex::parse_position(&json[1], &markets).unwrap();
+        assert_eq!((position.side, position.contracts), (Some(PositionSide::Long), 10.0));
+        assert_eq!(position.leverage, Some(5.0));
+        assert_eq!(position.margin, None);
+    }
+
+    #[test]
+    fn test_parse_funding_interval() {
+        let interval: serde_json::Value = serde_json::from_str(r#""2000-01-01T08:00:00.000Z""#).unwrap();
+        assert_eq!(Bitmex::parse_funding_interval(&interval), Some(8 * 3600 * 1000));
+        assert_eq!(Bitmex::parse_funding_interval(&serde_json::Value::Null), None);
+    }
+
+    #[test]
+    fn test_parse_insurance_fund() {
+        let fund: serde_json::Value = serde_json::from_str(r#"{
+            "currency": "XBt", "timestamp": "2018-10-20T12:00:00.000Z", "walletBalance": 2151541862934
+        }"#).unwrap();
+        let fund = Bitmex::parse_insurance_fund(&fund).unwrap();
+        assert_eq!(fund.currency, "BTC");
+        assert_eq!(fund.timestamp, 1540036800000);
+        assert_eq!(fund.balance, 2151541862934.0 * 0.00000001);
+    }
+
+    #[test]
+    fn test_parse_settlement() {
+        let settlement: serde_json::Value = serde_json::from_str(r#"{
+            "timestamp": "2018-09-28T12:00:00.000Z", "symbol": "XBTU18", "settlementType": "Settlement",
+            "settledPrice": 6614.38, "optionStrikePrice": null, "bankrupt": 0, "taxBase": 0, "taxRate": 0
+        }"#).unwrap();
+        let settlement = Bitmex::parse_settlement(&settlement, &std::collections::HashMap::new()).unwrap();
+        assert_eq!(settlement.symbol, "XBTU18");
+        assert_eq!(settlement.timestamp, 1538136000000);
+        assert_eq!(settlement.settlement_type, "Settlement");
+        assert_eq!(settlement.settled_price, Some(6614.38));
     }
 }
\ No newline at end of file
@@ -0,0 +1,953 @@
+use std::sync::Once;
+use super::prelude::*;
+use super::base::crypto::{sha256, hmac_sha512, base64_encode, base64_decode};
+use std::collections::HashMap;
+use futures::Future;
+use futures::future::{ok, err};
+use serde_json::Value;
+
+static INIT: Once = Once::new();
+static mut KRAKEN_EXCHANGE: Option<Exchange<HttpConnector>> = None;
+
+#[derive(Clone)]
+pub struct Kraken {
+    exchange: Exchange<HttpConnector>,
+}
+
+impl Kraken {
+
+    pub fn new() -> CCXTFut<Self> {
+        INIT.call_once(||{
+            unsafe {
+                KRAKEN_EXCHANGE = Some(Exchange::<HttpConnector>::from_json(r#"
+                {
+                    "id": "kraken",
+                    "name": "Kraken",
+                    "rateLimit": 3000,
+                    "has": {
+                        "fetchOHLCV": true,
+                        "fetchTickers": true,
+                        "fetchMyTrades": true,
+                        "fetchOrder": true,
+                        "fetchOpenOrders": true,
+                        "fetchClosedOrders": true,
+                        "fetchLedger": true,
+                        "fetchPositions": true
+                    },
+                    "api-urls": {
+                        "public": "https://api.kraken.com/0/public",
+                        "private": "https://api.kraken.com/0/private"
+                    },
+                    "api": {
+                        "public": {
+                            "get": [
+                                "Assets",
+                                "AssetPairs",
+                                "Depth",
+                                "OHLC",
+                                "Spread",
+                                "Ticker",
+                                "Time",
+                                "Trades"
+                            ]
+                        },
+                        "private": {
+                            "post": [
+                                "AddOrder",
+                                "Balance",
+                                "CancelOrder",
+                                "ClosedOrders",
+                                "DepositAddresses",
+                                "DepositMethods",
+                                "DepositStatus",
+                                "Ledgers",
+                                "OpenOrders",
+                                "OpenPositions",
+                                "QueryLedgers",
+                                "QueryOrders",
+                                "QueryTrades",
+                                "TradeBalance",
+                                "TradesHistory",
+                                "TradeVolume",
+                                "Withdraw",
+                                "WithdrawCancel",
+                                "WithdrawInfo",
+                                "WithdrawStatus"
+                            ]
+                        }
+                    },
+                    "exceptions": {
+                        "exact": {
+                            "EFunding:Unknown withdraw key": "ExchangeError",
+                            "EFunding:Invalid amount": "InsufficientFunds",
+                            "EService:Unavailable": "ExchangeNotAvailable",
+                            "EDatabase:Internal error": "ExchangeNotAvailable",
+                            "EService:Busy": "ExchangeNotAvailable",
+                            "EAPI:Rate limit exceeded": "DDoSProtection",
+                            "EAPI:Invalid nonce": "InvalidNonce",
+                            "EAPI:Invalid key": "AuthenticationError",
+                            "EAPI:Invalid signature": "AuthenticationError",
+                            "EQuery:Unknown asset": "ExchangeError",
+                            "EGeneral:Internal error": "ExchangeNotAvailable",
+                            "EGeneral:Temporary lockout": "DDoSProtection",
+                            "EOrder:Unknown order": "OrderNotFound",
+                            "EOrder:Insufficient funds": "InsufficientFunds",
+                            "EOrder:Insufficient margin": "InsufficientFunds"
+                        },
+                        "broad": {
+                            "EOrder:Invalid": "InvalidOrder",
+                            "EOrder:Orders limit exceeded": "InvalidOrder"
+                        }
+                    },
+                    "commonCurrencies": {
+                        "XBT": "BTC",
+                        "XDG": "DOGE",
+                        "BCC": "BCH",
+                        "DRK": "DASH"
+                    }
+                }
+            "#).unwrap())
+            }
+        });
+        let connector = HttpConnector::new();
+        let mut exchange = unsafe {KRAKEN_EXCHANGE.as_ref().unwrap().clone()};
+        exchange.set_connector(Box::new(connector));
+        exchange.set_sign_hook(Self::sign);
+        Box::from(Self::fetch_assets(&exchange)
+            .and_then(move |assets| -> CCXTFut<Self> {
+                for (id, code) in assets {
+                    exchange.common_currencies.insert(id, code);
+                }
+                let mut exchange = Kraken { exchange };
+                Box::from(exchange.fetch_markets().map(move |_| exchange))
+            }))
+    }
+
+    pub fn set_credentials(&mut self, credentials: Credentials) {
+        self.exchange.set_credentials(credentials);
+    }
+
+    ///
+    /// Asset ids are prefixed ISO4217-A3 codes (XXBT, ZUSD), the unified code is the common code of the altname (XBT -> BTC)
+    ///
+    fn fetch_assets(exchange: &Exchange<HttpConnector>) -> CCXTFut<Vec<(String, String)>> {
+        let currencies = exchange.common_currencies.clone();
+        Box::from(get_api!(exchange, "public", "Assets")
+            .and_then(move |json| Self::parse_assets(json, &currencies)))
+    }
+
+    fn parse_assets(json: Value, currencies: &HashMap<String, String>) -> Result<Vec<(String, String)>, Error> {
+        let mut assets = Vec::new();
+        for (id, asset) in as_object!(Self::result(json)?, "assets")? {
+            let code = common_currency_code(currencies, as_str!(asset["altname"], "asset->altname")?);
+            assets.push((id.clone(), code));
+        }
+        Ok(assets)
+    }
+
+    ///
+    /// Private requests are signed with HMAC-SHA512(base64 decoded secret, path + SHA256(nonce + body))
+    /// See https://www.kraken.com/features/api#general-usage
+    ///
+    fn sign(exchange: &Exchange<HttpConnector>, api: &str, request: &mut Request) -> Result<(), Error> {
+        if api != "private" {
+            return Ok(());
+        }
+        exchange.check_required_credentials()?;
+        let credentials = exchange.credentials();
+        let api_key = credentials.api_key.clone().unwrap_or_default();
+        let secret = base64_decode(credentials.secret.as_ref().map_or("", |secret| secret.as_str())).map_err(|_| CCXTError::AuthenticationError)?;
+        Self::sign_request(request, &api_key, &secret, exchange.nonce());
+        Ok(())
+    }
+
+    ///
+    /// The nonce is sent as the first parametter of the post data
+    ///
+    fn sign_request(request: &mut Request, api_key: &str, secret: &[u8], nonce: i64) {
+        request.insert_param(0, format!("nonce={}", nonce));
+        let mut auth = request.path.path().as_bytes().to_vec();
+        auth.extend(sha256(format!("{}{}", nonce, request.encoded_params()).as_bytes()));
+        let signature = base64_encode(&hmac_sha512(secret, &auth));
+        request.add_header("API-Key", api_key);
+        request.add_header("API-Sign", signature.as_str());
+    }
+
+    ///
+    /// Kraken answer {"error": [...], "result": ...}, known errors are already mapped by the exceptions
+    ///
+    fn result(json: Value) -> Result<Value, Error> {
+        match json {
+            Value::Object(mut json) => {
+                if json.get("error").and_then(Value::as_array).map_or(false, |errors| !errors.is_empty()) {
+                    return Err(CCXTError::ExchangeError.into());
+                }
+                Ok(json.remove("result").ok_or(CCXTError::BadResponse)?)
+            },
+            _ => Err(CCXTError::BadResponse.into()),
+        }
+    }
+
+    fn time_frame(time: CandleTime) -> Option<&'static str> {
+        match time {
+            CandleTime::_1M => Some("1"),
+            CandleTime::_5M => Some("5"),
+            CandleTime::_15M => Some("15"),
+            CandleTime::_30M => Some("30"),
+            CandleTime::_1H => Some("60"),
+            CandleTime::_4H => Some("240"),
+            CandleTime::_1D => Some("1440"),
+            CandleTime::_1W => Some("10080"),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Orders reference the pair altname (XBTUSD) and other endpoints the pair id (XXBTZUSD)
+    ///
+    fn find_market<'a>(markets: &'a HashMap<String, Market>, id: &str) -> Option<&'a Market> {
+        market_by_id(markets, id).or_else(|| markets.values().find(|market| market.info.as_ref().map_or(false, |info| info["altname"].as_str() == Some(id))))
+    }
+
+    ///
+    /// Same as symbol_from_id but the pair can also be an altname
+    ///
+    fn symbol_from_pair(markets: &HashMap<String, Market>, id: &str) -> String {
+        Self::find_market(markets, id).map_or_else(|| String::from(id), |market| market.symbol.clone())
+    }
+
+    fn is_darkpool(market: &Market) -> bool {
+        market.id.contains(".d")
+    }
+
+    ///
+    /// Amounts are truncated to the market precision, prices are rounded
+    ///
+    fn price_to_precision(market: &Market, price: f64) -> String {
+        format!("{:.*}", market.precision.1 as usize, price)
+    }
+
+    ///
+    /// Darkpool pairs (id ending with .d) use the altname as symbol
+    /// The pair info is kept for the altname lookups and the allowed leverages
+    ///
+    fn parse_markets(re: Value, currencies: &HashMap<String, String>) -> Result<HashMap<String, Market>, Error> {
+        let mut markets = HashMap::<String, Market>::new();
+        for (id, market) in as_object!(Self::result(re)?, "markets")? {
+            let base_id = String::from(as_str!(market["base"], "market->base")?);
+            let quote_id = String::from(as_str!(market["quote"], "market->quote")?);
+            let base = common_currency_code(currencies, &base_id);
+            let quote = common_currency_code(currencies, &quote_id);
+            let symbol = if id.contains(".d") {
+                String::from(as_str!(market["altname"], "market->altname")?)
+            } else {
+                format!("{}/{}", base, quote)
+            };
+            let precision = (as_i64_or!(market["lot_decimals"], 8) as f64, as_i64_or!(market["pair_decimals"], 8) as f64);
+            markets.insert(symbol.clone(), Market {
+                id: id.clone(),
+                symbol,
+                base,
+                quote,
+                base_id,
+                quote_id,
+                active: true,
+                precision,
+                limits: MarketLimits::new((10f64.powf(-precision.0), 10f64.powf(precision.0)), (10f64.powf(-precision.1), 0.0), (0.0, 0.0)),
+                market_type: MarketType::Spot,
+                linear: None,
+                settle: None,
+                contract_size: None,
+                expiry: None,
+                underlying_index: None,
+                info: Some(market.clone()),
+            });
+        }
+        Ok(markets)
+    }
+
+    ///
+    /// Candles are [time, open, high, low, close, vwap, volume, count], time is in seconds
+    ///
+    fn parse_ohlcv(json: &Value) -> Result<Vec<Ohlcv>, Error> {
+        let mut ohlcv = Vec::<Ohlcv>::new();
+        for candle in as_array!(json, "ohlcv")? {
+            ohlcv.push(Ohlcv {
+                timestamp: as_i64!(candle[0], "ohlcv->time")?,
+                open: safe_f64!(candle[1]).ok_or(CCXTError::BadResponse)?,
+                highest: safe_f64!(candle[2]).ok_or(CCXTError::BadResponse)?,
+                lowest: safe_f64!(candle[3]).ok_or(CCXTError::BadResponse)?,
+                losing: safe_f64!(candle[4]).ok_or(CCXTError::BadResponse)?,
+                volume: safe_f64!(candle[6]).ok_or(CCXTError::BadResponse)?,
+            });
+        }
+        Ok(ohlcv)
+    }
+
+    ///
+    /// Values are [today, last 24 hours] except a, b and c which are [price, whole lot volume, lot volume]
+    ///
+    fn parse_ticker(ticker: &Value, symbol: String) -> Ticker {
+        let base_volume = safe_f64!(ticker["v"][1]);
+        let vwap = safe_f64!(ticker["p"][1]);
+        let last = safe_f64!(ticker["c"][0]);
+        Ticker {
+            symbol,
+            timestamp: milliseconds(),
+            high: safe_f64!(ticker["h"][1]),
+            low: safe_f64!(ticker["l"][1]),
+            bid: safe_f64!(ticker["b"][0]),
+            bid_volume: None,
+            ask: safe_f64!(ticker["a"][0]),
+            ask_volume: None,
+            vwap,
+            open: safe_f64!(ticker["o"]),
+            close: last,
+            last,
+            previous_close: None,
+            change: None,
+            percentage: None,
+            average: None,
+            base_volume,
+            quote_volume: base_volume.and_then(|volume| vwap.map(|vwap| volume * vwap)),
+            info: Some(ticker.clone()),
+        }
+    }
+
+    ///
+    /// Parse a public trade [price, volume, time, side, type, misc] or an account trade
+    /// The fee of account trades is in the quote currency
+    ///
+    fn parse_trade(trade: &Value, id: Option<String>, market: Option<&Market>) -> Result<Trade, Error> {
+        let symbol = market.map_or_else(|| safe_string!(trade["pair"]).unwrap_or_default(), |market| market.symbol.clone());
+        if trade.is_array() {
+            let price = safe_f64!(trade[0]).ok_or(CCXTError::BadResponse)?;
+            let amount = safe_f64!(trade[1]).ok_or(CCXTError::BadResponse)?;
+            return Ok(Trade {
+                id,
+                timestamp: (as_f64!(trade[2], "trade->time")? * 1000.0) as i64,
+                symbol,
+                order: None,
+                side: Some(if trade[3].as_str() == Some("s") { OrderSide::Sell } else { OrderSide::Buy }),
+                taker_or_maker: None,
+                price,
+                amount,
+                cost: price * amount,
+                fee: None,
+                info: Some(trade.clone()),
+            });
+        }
+        let price = safe_f64!(trade["price"]).ok_or(CCXTError::BadResponse)?;
+        let amount = safe_f64!(trade["vol"]).ok_or(CCXTError::BadResponse)?;
+        Ok(Trade {
+            id,
+            timestamp: (as_f64!(trade["time"], "trade->time")? * 1000.0) as i64,
+            symbol,
+            order: safe_string!(trade["ordertxid"]),
+            side: trade["type"].as_str().and_then(|side| side.parse().ok()),
+            taker_or_maker: None,
+            price,
+            amount,
+            cost: safe_f64!(trade["cost"]).unwrap_or(price * amount),
+            fee: safe_f64!(trade["fee"]).map(|cost| Fee {
+                cost,
+                currency: market.map(|market| market.quote.clone()),
+                rate: None,
+            }),
+            info: Some(trade.clone()),
+        })
+    }
+
+    fn parse_order_status(status: &str) -> Result<OrderStatus, Error> {
+        match status {
+            "pending" | "open" => Ok(OrderStatus::Open),
+            "closed" => Ok(OrderStatus::Closed),
+            "canceled" => Ok(OrderStatus::Canceled),
+            "expired" => Ok(OrderStatus::Expired),
+            _ => Err(CCXTError::BadResponse.into()),
+        }
+    }
+
+    ///
+    /// The price of the description is 0 for market orders, the average price is used instead
+    /// oflags fciq and fcib tell if the fee is paid in the quote or the base currency
+    ///
+    fn parse_order(id: &str, order: &Value, markets: &HashMap<String, Market>) -> Result<Order, Error> {
+        let description = &order["descr"];
+        let market = Self::find_market(markets, as_str!(description["pair"], "order->descr->pair")?);
+        let amount = safe_f64!(order["vol"]).unwrap_or(0.0);
+        let filled = safe_f64!(order["vol_exec"]).unwrap_or(0.0);
+        let price = safe_f64!(description["price"]).filter(|price| *price != 0.0)
+            .or_else(|| safe_f64!(description["price2"]).filter(|price| *price != 0.0))
+            .or_else(|| safe_f64!(order["price"]));
+        let flags = order["oflags"].as_str().unwrap_or("");
+        let fee_currency = market.and_then(|market| {
+            if flags.contains("fciq") {
+                Some(market.quote.clone())
+            } else if flags.contains("fcib") {
+                Some(market.base.clone())
+            } else {
+                None
+            }
+        });
+        Ok(Order {
+            id: String::from(id),
+            client_order_id: safe_string!(order["userref"]),
+            timestamp: order["opentm"].as_f64().map(|time| (time * 1000.0) as i64),
+            last_trade_timestamp: order["closetm"].as_f64().map(|time| (time * 1000.0) as i64),
+            symbol: market.map_or_else(|| safe_string!(description["pair"]).unwrap_or_default(), |market| market.symbol.clone()),
+            order_type: as_str!(description["ordertype"], "order->descr->ordertype")?.parse()?,
+            side: as_str!(description["type"], "order->descr->type")?.parse()?,
+            price,
+            amount,
+            filled,
+            remaining: (amount - filled).max(0.0),
+            cost: safe_f64!(order["cost"]).unwrap_or(0.0),
+            status: Self::parse_order_status(as_str!(order["status"], "order->status")?)?,
+            fee: safe_f64!(order["fee"]).map(|cost| Fee {
+                cost,
+                currency: fee_currency,
+                rate: None,
+            }),
+            trades: Vec::new(),
+            info: Some(order.clone()),
+        })
+    }
+
+    ///
+    /// Orders are returned as an object indexed by transaction id
+    ///
+    fn parse_orders(orders: &Value, markets: &HashMap<String, Market>, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> Result<Vec<Order>, Error> {
+        let mut result = Vec::new();
+        for (id, order) in as_object!(orders, "orders")? {
+            result.push(Self::parse_order(id, order, markets)?);
+        }
+        result.sort_by_key(|order| order.timestamp);
+        Ok(filter_by_symbol_since_limit(result, symbol, since, limit))
+    }
+
+    fn parse_ledger_entry(id: &str, entry: &Value, currencies: &HashMap<String, String>) -> Result<LedgerEntry, Error> {
+        let currency = common_currency_code(currencies, as_str!(entry["asset"], "ledger->asset")?);
+        let amount = safe_f64!(entry["amount"]).ok_or(CCXTError::BadResponse)?;
+        let fee = safe_f64!(entry["fee"]).unwrap_or(0.0);
+        let after = safe_f64!(entry["balance"]);
+        let entry_type = match as_str!(entry["type"], "ledger->type")? {
+            "trade" => LedgerEntryType::Trade,
+            "deposit" | "withdrawal" => LedgerEntryType::Transaction,
+            "margin" | "rollover" | "settled" => LedgerEntryType::Margin,
+            "transfer" => LedgerEntryType::Transfer,
+            other => LedgerEntryType::Other(String::from(other)),
+        };
+        Ok(LedgerEntry {
+            id: String::from(id),
+            timestamp: entry["time"].as_f64().map(|time| (time * 1000.0) as i64),
+            direction: if amount < 0.0 { LedgerDirection::Out } else { LedgerDirection::In },
+            account: None,
+            reference_id: safe_string!(entry["refid"]),
+            reference_account: None,
+            entry_type,
+            amount: amount.abs(),
+            before: after.map(|after| after - amount + fee),
+            after,
+            status: Some(TransactionStatus::Ok),
+            fee: Some(Fee {
+                cost: fee,
+                currency: Some(currency.clone()),
+                rate: None,
+            }),
+            currency,
+            info: Some(entry.clone()),
+        })
+    }
+
+    ///
+    /// Margin positions are opened by orders with leverage, the values are computed with docalc
+    ///
+    fn parse_position(position: &Value, markets: &HashMap<String, Market>) -> Result<Position, Error> {
+        let volume = safe_f64!(position["vol"]).unwrap_or(0.0);
+        let contracts = volume - safe_f64!(position["vol_closed"]).unwrap_or(0.0);
+        let cost = safe_f64!(position["cost"]);
+        let margin = safe_f64!(position["margin"]);
+        Ok(Position {
+            symbol: Self::symbol_from_pair(markets, as_str!(position["pair"], "position->pair")?),
+            timestamp: position["time"].as_f64().map(|time| (time * 1000.0) as i64),
+            side: match position["type"].as_str() {
+                Some("buy") => Some(PositionSide::Long),
+                Some("sell") => Some(PositionSide::Short),
+                _ => None,
+            },
+            contracts,
+            entry_price: cost.filter(|_| volume > 0.0).map(|cost| cost / volume),
+            mark_price: None,
+            liquidation_price: None,
+            leverage: cost.and_then(|cost| margin.filter(|margin| *margin > 0.0).map(|margin| cost / margin)),
+            notional: safe_f64!(position["value"]).or(cost),
+            margin,
+            unrealized_pnl: safe_f64!(position["net"]),
+            realized_pnl: None,
+            info: Some(position.clone()),
+        })
+    }
+
+    ///
+    /// AddOrder only return the transaction ids, the order is built from the request
+    /// leverage must be one of the leverage_buy or leverage_sell values of the pair
+    ///
+    fn add_order(&self, symbol: &str, order_type: OrderType, side: OrderSide, amount: f64, price: Option<f64>, leverage: Option<f64>) -> OrderResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let kraken_type = match &order_type {
+            OrderType::Market => String::from("market"),
+            OrderType::Limit => String::from("limit"),
+            OrderType::Stop => String::from("stop-loss"),
+            OrderType::StopLimit => return Box::new(err(CCXTError::InvalidOrder.into())),
+            OrderType::Other(order_type) => order_type.clone(),
+        };
+        let kraken_side = match side {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        };
+        let mut params = vec![
+            format!("pair={}", market.id),
+            format!("type={}", kraken_side),
+            format!("ordertype={}", kraken_type),
+            format!("volume={}", truncate_to_precision(amount, market.precision.0 as usize)),
+        ];
+        match (&order_type, price) {
+            (OrderType::Market, _) => {},
+            (OrderType::Limit, None) => return Box::new(err(CCXTError::InvalidOrder.into())),
+            (_, Some(price)) => params.push(format!("price={}", Self::price_to_precision(&market, price))),
+            (_, None) => {},
+        }
+        if let Some(leverage) = leverage {
+            let key = if side == OrderSide::Buy { "leverage_buy" } else { "leverage_sell" };
+            let allowed = market.info.as_ref()
+                .and_then(|info| info[key].as_array())
+                .map_or(false, |levels| levels.iter().any(|level| level.as_f64() == Some(leverage)));
+            if !allowed {
+                return Box::new(err(CCXTError::InvalidOrder.into()));
+            }
+            params.push(format!("leverage={}", leverage));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "AddOrder", &params)
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let id = as_array!(result["txid"], "order->txid")?.iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<&str>>()
+                    .join(",");
+                Ok(Order {
+                    id,
+                    client_order_id: None,
+                    timestamp: Some(milliseconds()),
+                    last_trade_timestamp: None,
+                    symbol: market.symbol,
+                    order_type,
+                    side,
+                    price,
+                    amount,
+                    filled: 0.0,
+                    remaining: amount,
+                    cost: 0.0,
+                    status: OrderStatus::Open,
+                    fee: None,
+                    trades: Vec::new(),
+                    info: Some(result),
+                })
+            }))
+    }
+
+    ///
+    /// Open a margin position, see add_order for the accepted leverages
+    ///
+    pub fn create_margin_order(&self, symbol: &str, order_type: OrderType, side: OrderSide, amount: f64, price: Option<f64>, leverage: f64) -> OrderResult {
+        self.add_order(symbol, order_type, side, amount, price, Some(leverage))
+    }
+
+}
+
+impl ExchangeTrait for Kraken {
+
+    fn has(&self, capability: &str) -> bool {
+        self.exchange.has(capability)
+    }
+
+    ///
+    /// since is in seconds like the returned timestamps, Kraken return at most 720 candles
+    ///
+    fn fetch_ohlcv(&self, symbol: &str, timeframe: CandleTime, since: i64, limit: i64) -> FetchOhlcvResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let interval = try_future_box!(Self::time_frame(timeframe).ok_or(CCXTError::NotSupported));
+        let pair = format!("pair={}", market.id);
+        let interval = format!("interval={}", interval);
+        let start = format!("since={}", since);
+        Box::from(get_api!(self.exchange, "public", "OHLC", pair.as_str(), interval.as_str(), start.as_str())
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let mut ohlcv = Self::parse_ohlcv(&result[&market.id])?;
+                ohlcv.truncate(limit.max(0) as usize);
+                Ok(ohlcv)
+            }))
+    }
+
+    fn fetch_ticker(&self, symbol: &str) -> FetchTickerResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        if Self::is_darkpool(&market) {
+            return Box::new(err(CCXTError::ExchangeError.into()));
+        }
+        let pair = format!("pair={}", market.id);
+        Box::from(get_api!(self.exchange, "public", "Ticker", pair.as_str())
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                Ok(Self::parse_ticker(&result[&market.id], market.symbol))
+            }))
+    }
+
+    ///
+    /// Darkpool and inactive markets have no ticker
+    ///
+    fn fetch_tickers(&self, symbols: Option<&[&str]>) -> FetchTickersResult {
+        let symbols: Option<Vec<String>> = symbols.map(|symbols| symbols.iter().map(|symbol| String::from(*symbol)).collect());
+        let pairs = {
+            let markets = self.exchange.market.read().unwrap();
+            let markets = try_future_box!(markets.as_ref().ok_or(CCXTError::ExchangeError));
+            markets.values()
+                .filter(|market| market.active && !Self::is_darkpool(market))
+                .filter(|market| symbols.as_ref().map_or(true, |symbols| symbols.contains(&market.symbol)))
+                .map(|market| market.id.clone())
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let pair = format!("pair={}", pairs);
+        let lock = self.exchange.market.clone();
+        Box::from(get_api!(self.exchange, "public", "Ticker", pair.as_str())
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut tickers = HashMap::<String, Ticker>::new();
+                for (id, ticker) in as_object!(result, "tickers")? {
+                    let symbol = Self::symbol_from_pair(markets, id);
+                    tickers.insert(symbol.clone(), Self::parse_ticker(ticker, symbol));
+                }
+                Ok(tickers)
+            }))
+    }
+
+    fn fetch_order_book(&self, symbol: &str, limit: Option<i64>) -> FetchOrderBookResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        if Self::is_darkpool(&market) {
+            return Box::new(err(CCXTError::ExchangeError.into()));
+        }
+        let mut params = vec![format!("pair={}", market.id)];
+        if let Some(limit) = limit {
+            params.push(format!("count={}", limit));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "Depth", &params)
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                Ok(parse_order_book(&result[&market.id], None, "bids", "asks", &0, &1))
+            }))
+    }
+
+    ///
+    /// Public trades have no id, the "last" cursor is used as the id of the most recent one
+    /// since is in milliseconds, Kraken expect nanoseconds
+    ///
+    fn fetch_trades(&self, symbol: &str, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let market = try_future_box!(self.exchange.market(symbol));
+        let mut params = vec![format!("pair={}", market.id)];
+        if let Some(since) = since {
+            params.push(format!("since={}", since * 1000000));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        Box::from(self.exchange.call_api("public", ApiMethod::Get, "Trades", &params)
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let trades = as_array!(result[&market.id], "trades")?;
+                let last = safe_string!(result["last"]);
+                let mut parsed = Vec::new();
+                for (index, trade) in trades.iter().enumerate() {
+                    let id = if index + 1 == trades.len() { last.clone() } else { None };
+                    parsed.push(Self::parse_trade(trade, id, Some(&market))?);
+                }
+                Ok(filter_by_symbol_since_limit(parsed, None, since, limit))
+            }))
+    }
+
+    fn fetch_my_trades(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchTradesResult {
+        let symbol = symbol.map(String::from);
+        let mut params = Vec::new();
+        if let Some(since) = since {
+            params.push(format!("start={}", since / 1000));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "TradesHistory", &params)
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut trades = Vec::new();
+                for (id, trade) in as_object!(result["trades"], "mytrades")? {
+                    let market = trade["pair"].as_str().and_then(|pair| Self::find_market(markets, pair));
+                    trades.push(Self::parse_trade(trade, Some(id.clone()), market)?);
+                }
+                trades.sort_by_key(|trade| trade.timestamp);
+                Ok(filter_by_symbol_since_limit(trades, symbol.as_ref().map(String::as_str), since, limit))
+            }))
+    }
+
+    fn fetch_currencies(&self) -> FetchCurrenciesResult {
+        Box::new(ok(self.exchange.currencies_from_markets()))
+    }
+
+    ///
+    /// Kraken only return the total of each asset
+    ///
+    fn fetch_balance(&self) -> FetchBalanceResult {
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(post_api!(self.exchange, "private", "Balance")
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let mut balance = Balance::default();
+                for (id, total) in as_object!(result, "balance")? {
+                    let code = common_currency_code(&currencies, id);
+                    balance.currencies.insert(code, CurrencyBalance::new(safe_f64!(total), Some(0.0), None));
+                }
+                balance.info = Some(result);
+                Ok(balance)
+            }))
+    }
+
+    fn create_order(&self, symbol: &str, order_type: OrderType, side: OrderSide, amount: f64, price: Option<f64>) -> OrderResult {
+        self.add_order(symbol, order_type, side, amount, price, None)
+    }
+
+    ///
+    /// CancelOrder only return the number of canceled orders, the order is queried afterward
+    ///
+    fn cancel_order(&self, id: &str, _symbol: Option<&str>) -> OrderResult {
+        let kraken = self.clone();
+        let order_id = String::from(id);
+        let txid = format!("txid={}", id);
+        Box::from(post_api!(self.exchange, "private", "CancelOrder", txid.as_str())
+            .and_then(|json| Self::result(json))
+            .and_then(move |_| kraken.fetch_order(&order_id, None)))
+    }
+
+    fn fetch_order(&self, id: &str, _symbol: Option<&str>) -> OrderResult {
+        let order_id = String::from(id);
+        let txid = format!("txid={}", id);
+        let lock = self.exchange.market.clone();
+        Box::from(post_api!(self.exchange, "private", "QueryOrders", txid.as_str(), "trades=true")
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let markets = lock.read().unwrap();
+                let order = result.get(&order_id).ok_or(CCXTError::OrderNotFound)?;
+                Self::parse_order(&order_id, order, markets.as_ref().ok_or(CCXTError::ExchangeError)?)
+            }))
+    }
+
+    fn fetch_open_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        let symbol = symbol.map(String::from);
+        let lock = self.exchange.market.clone();
+        Box::from(post_api!(self.exchange, "private", "OpenOrders")
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let markets = lock.read().unwrap();
+                Self::parse_orders(&result["open"], markets.as_ref().ok_or(CCXTError::ExchangeError)?, symbol.as_ref().map(String::as_str), since, limit)
+            }))
+    }
+
+    fn fetch_closed_orders(&self, symbol: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchOrdersResult {
+        let symbol = symbol.map(String::from);
+        let mut params = Vec::new();
+        if let Some(since) = since {
+            params.push(format!("start={}", since / 1000));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let lock = self.exchange.market.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "ClosedOrders", &params)
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let markets = lock.read().unwrap();
+                Self::parse_orders(&result["closed"], markets.as_ref().ok_or(CCXTError::ExchangeError)?, symbol.as_ref().map(String::as_str), since, limit)
+            }))
+    }
+
+    ///
+    /// Kraken return at most 50 entries per request
+    ///
+    fn fetch_ledger(&self, code: Option<&str>, since: Option<i64>, limit: Option<i64>) -> FetchLedgerResult {
+        let mut params = Vec::new();
+        if let Some(code) = code {
+            let id = try_future_box!(self.exchange.currency_id(code).ok_or(CCXTError::ExchangeError));
+            params.push(format!("asset={}", id));
+        }
+        if let Some(since) = since {
+            params.push(format!("start={}", since / 1000));
+        }
+        let params: Vec<&str> = params.iter().map(|param| param.as_str()).collect();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(self.exchange.call_api("private", ApiMethod::Post, "Ledgers", &params)
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let mut entries = Vec::new();
+                for (id, entry) in as_object!(result["ledger"], "ledger")? {
+                    entries.push(Self::parse_ledger_entry(id, entry, &currencies)?);
+                }
+                entries.sort_by_key(|entry| entry.timestamp);
+                Ok(filter_by_currency_since_limit(entries, None, since, limit))
+            }))
+    }
+
+    fn fetch_positions(&self, symbols: Option<&[&str]>) -> FetchPositionsResult {
+        let symbols: Option<Vec<String>> = symbols.map(|symbols| symbols.iter().map(|symbol| String::from(*symbol)).collect());
+        let lock = self.exchange.market.clone();
+        Box::from(post_api!(self.exchange, "private", "OpenPositions", "docalc=true")
+            .and_then(move |json| {
+                let result = Self::result(json)?;
+                let markets = lock.read().unwrap();
+                let markets = markets.as_ref().ok_or(CCXTError::ExchangeError)?;
+                let mut positions = Vec::new();
+                for position in as_object!(result, "positions")?.values() {
+                    let position = Self::parse_position(position, markets)?;
+                    if symbols.as_ref().map_or(true, |symbols| symbols.contains(&position.symbol)) {
+                        positions.push(position);
+                    }
+                }
+                positions.sort_by_key(|position| position.timestamp);
+                Ok(positions)
+            }))
+    }
+
+    fn fetch_markets(&mut self) -> LoadMarketResult {
+        let lock = self.exchange.market.clone();
+        let currencies = self.exchange.common_currencies.clone();
+        Box::from(get_api!(self.exchange, "public", "AssetPairs")
+            .and_then(move |re| {
+                let markets = Self::parse_markets(re, &currencies)?;
+                *lock.write().unwrap() = Some(markets);
+                Ok(lock)
+            }))
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+    use tokio::runtime::current_thread::block_on_all;
+    use super::Kraken;
+    use futures::Future;
+    use crate::prelude::*;
+    use crate::base::exchange::{ExchangeTrait, Request, RequestMethod, RequestBody};
+    use crate::base::crypto::base64_decode;
+    #[test]
+    #[ignore]
+    fn test_plateform() {
+        let ohlcv = block_on_all(Kraken::new().and_then(|exchange| {
+            exchange.fetch_ohlcv("BTC/USD", CandleTime::_1H, 1540000000, 100)
+        })).unwrap();
+        assert!(!ohlcv.is_empty());
+        assert!(ohlcv.iter().all(|candle| candle.timestamp >= 1540000000 && candle.lowest <= candle.highest));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ticker() {
+        let ticker = block_on_all(Kraken::new().and_then(|exchange| exchange.fetch_ticker("BTC/USD"))).unwrap();
+        assert_eq!(ticker.symbol, "BTC/USD");
+        assert!(ticker.bid.unwrap() <= ticker.ask.unwrap());
+        assert!(ticker.last.is_some());
+    }
+
+    #[test]
+    fn test_sign_request() {
+        //Example of https://docs.kraken.com/rest/#section/Authentication/Headers-and-Signature
+        let params = vec!["ordertype=limit", "pair=XBTUSD", "price=37500", "type=buy", "volume=1.25"];
+        let params = params.into_iter().map(String::from).collect();
+        let mut request = Request::new("https://api.kraken.com/0/private/AddOrder".parse().unwrap(), RequestMethod::Post(Vec::new(), RequestBody::UrlEncoded(params)));
+        let secret = base64_decode("kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==").unwrap();
+        Kraken::sign_request(&mut request, "key", &secret, 1616492376594);
+        assert_eq!(request.encoded_params(), "nonce=1616492376594&ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25");
+        assert!(request.headers.contains(&(String::from("API-Sign"),
+            String::from("4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ=="))));
+    }
+
+    #[test]
+    fn test_parse_markets() {
+        let assets: serde_json::Value = serde_json::from_str(r#"{"error": [], "result": {
+            "XXBT": {"aclass": "currency", "altname": "XBT", "decimals": 10},
+            "ZUSD": {"aclass": "currency", "altname": "USD", "decimals": 4}
+        }}"#).unwrap();
+        let pairs: serde_json::Value = serde_json::from_str(r#"{"error": [], "result": {
+            "XXBTZUSD": {"altname": "XBTUSD", "base": "XXBT", "quote": "ZUSD", "lot_decimals": 8, "pair_decimals": 1, "leverage_buy": [2, 3]},
+            "XXBTZUSD.d": {"altname": "XBTUSD.d", "base": "XXBT", "quote": "ZUSD", "lot_decimals": 8, "pair_decimals": 1}
+        }}"#).unwrap();
+        let mut currencies = std::collections::HashMap::new();
+        currencies.insert(String::from("XBT"), String::from("BTC"));
+        for (id, code) in Kraken::parse_assets(assets, &currencies.clone()).unwrap() {
+            currencies.insert(id, code);
+        }
+        assert_eq!(currencies["XXBT"], "BTC");
+        assert_eq!(currencies["ZUSD"], "USD");
+        let markets = Kraken::parse_markets(pairs, &currencies).unwrap();
+        let btc = &markets["BTC/USD"];
+        assert_eq!((btc.id.as_str(), btc.base_id.as_str(), btc.quote_id.as_str()), ("XXBTZUSD", "XXBT", "ZUSD"));
+        assert_eq!(btc.precision, (8.0, 1.0));
+        assert_eq!(Kraken::symbol_from_pair(&markets, "XBTUSD"), "BTC/USD");
+        assert!(Kraken::is_darkpool(&markets["XBTUSD.d"]));
+    }
+
+    #[test]
+    fn test_parse_ledger_entry() {
+        let entry: serde_json::Value = serde_json::from_str(r#"{
+            "refid": "TJKLXX-PGMUI-4NTLXU", "time": 1688464484.1787, "type": "trade", "subtype": "", "aclass": "currency",
+            "asset": "XXBT", "amount": "-0.2500000000", "fee": "0.0010000000", "balance": "1.2490000000"
+        }"#).unwrap();
+        let mut currencies = std::collections::HashMap::new();
+        currencies.insert(String::from("XXBT"), String::from("BTC"));
+        let entry = Kraken::parse_ledger_entry("L4UESK-KG3EQ-UFO4T5", &entry, &currencies).unwrap();
+        assert_eq!(entry.currency, "BTC");
+        assert_eq!(entry.direction, LedgerDirection::Out);
+        assert_eq!(entry.entry_type, LedgerEntryType::Trade);
+        assert_eq!(entry.amount, 0.25);
+        assert_eq!(entry.after, Some(1.249));
+        assert_eq!(entry.before, Some(1.5));
+        assert_eq!(entry.reference_id.as_ref().map(String::as_str), Some("TJKLXX-PGMUI-4NTLXU"));
+        assert_eq!(entry.timestamp, Some(1688464484178));
+    }
+
+    #[test]
+    fn test_parse_ticker() {
+        let ticker: serde_json::Value = serde_json::from_str(r#"{
+            "a": ["6396.50000", "1", "1.000"], "b": ["6395.50000", "2", "2.000"], "c": ["6396.00000", "0.05000000"],
+            "v": ["1200.5", "2000.0"], "p": ["6420.1", "6450.0"], "t": [3000, 5000],
+            "l": ["6380.0", "6370.0"], "h": ["6500.0", "6520.0"], "o": "6490.9"
+        }"#).unwrap();
+        let ticker = Kraken::parse_ticker(&ticker, String::from("BTC/USD"));
+        assert_eq!(ticker.symbol, "BTC/USD");
+        assert_eq!((ticker.bid, ticker.ask), (Some(6395.5), Some(6396.5)));
+        assert_eq!((ticker.high, ticker.low), (Some(6520.0), Some(6370.0)));
+        assert_eq!((ticker.open, ticker.last, ticker.close), (Some(6490.9), Some(6396.0), Some(6396.0)));
+        assert_eq!((ticker.vwap, ticker.base_volume), (Some(6450.0), Some(2000.0)));
+        assert_eq!(ticker.quote_volume, Some(2000.0 * 6450.0));
+    }
+
+    #[test]
+    fn test_parse_ohlcv() {
+        let json: serde_json::Value = serde_json::from_str(r#"[
+            [1540000000, "6490.9", "6500.0", "6480.1", "6495.2", "6491.3", "12.5", 80],
+            [1540003600, "6495.2", "6510.0", "6490.0", "6501.0", "6500.4", "8.25", 61]
+        ]"#).unwrap();
+        let ohlcv = Kraken::parse_ohlcv(&json).unwrap();
+        assert_eq!(ohlcv.len(), 2);
+        assert_eq!(ohlcv[0].timestamp, 1540000000);
+        assert_eq!((ohlcv[0].open, ohlcv[0].highest, ohlcv[0].lowest, ohlcv[0].losing), (6490.9, 6500.0, 6480.1, 6495.2));
+        assert_eq!(ohlcv[0].volume, 12.5);
+        assert_eq!(ohlcv[1].volume, 8.25);
+    }
+}
@@ -5,6 +5,11 @@
 extern crate serde_derive;
 extern crate serde_json;
 extern crate chrono;
+extern crate hmac;
+extern crate sha2;
+extern crate hex;
+extern crate rand;
+extern crate base64;
 
 extern crate tokio;
 extern crate tokio_core;
@@ -22,5 +27,7 @@ pub mod prelude;
 
 #[macro_use]
 pub mod base;
-//pub mod bitfinex;
-pub mod bitmex;
\ No newline at end of file
+pub mod bitfinex;
+pub mod bitmex;
+pub mod binance;
+pub mod kraken;
\ No newline at end of file